@@ -0,0 +1,76 @@
+use bink_types::curve::{is_probable_prime, Curve};
+use num_bigint::{BigInt, RandBigInt};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::Rng;
+
+/// A full set of BINK parameters
+pub struct Bink {
+    pub curve: Curve,
+    pub g: (BigInt, BigInt),
+    pub n: BigInt,
+    pub private: BigInt,
+    pub public: (BigInt, BigInt),
+}
+
+/// Generates a curve over a `curve_bits` sized prime with a base point of prime order `n`, where
+/// `n` is `order_bits` long.
+///
+/// The curves are supersingular, y^2 = x^3 + b with p = 2 (mod 3), which have exactly p + 1
+/// points. Picking p = h * n - 1 puts a subgroup of order n on the curve without having to count
+/// points. Such curves are weak, which is fine for test data but nothing else.
+pub fn generate<R: Rng>(rng: &mut R, curve_bits: u64, order_bits: u64) -> Bink {
+    let n = random_prime(rng, order_bits);
+
+    // h must be a multiple of 6 so that p is odd and p = 2 (mod 3)
+    let (p, h) = loop {
+        let h = rng.gen_biguint(curve_bits - order_bits - 2);
+        let h = BigInt::from(h) * 6;
+        let p: BigInt = &h * &n - 1;
+        if p.bits() == curve_bits && is_probable_prime(&p) {
+            break (p, h);
+        }
+    };
+
+    let curve = Curve {
+        p: p.clone(),
+        a: BigInt::zero(),
+        b: rng.gen_bigint_range(&BigInt::one(), &p),
+    };
+
+    // Cubing is a bijection when p = 2 (mod 3), so every y has exactly one x
+    let cube_root = (BigInt::from(2) * &p - 1) / 3;
+    let g = loop {
+        let y = rng.gen_bigint_range(&BigInt::zero(), &p);
+        let x = (&y * &y - &curve.b).mod_floor(&p).modpow(&cube_root, &p);
+        if let Some(g) = curve.mul(&h, &Some((x, y))) {
+            break g;
+        }
+    };
+    let base = Some(g.clone());
+    debug_assert!(curve.contains(&base));
+    debug_assert!(curve.mul(&n, &base).is_none());
+
+    // Microsoft's keys are stored such that public = -private * G
+    let private = rng.gen_bigint_range(&BigInt::one(), &n);
+    let public = curve.neg(&curve.mul(&private, &base)).unwrap();
+
+    Bink {
+        curve,
+        g,
+        n,
+        private,
+        public,
+    }
+}
+
+fn random_prime<R: Rng>(rng: &mut R, bits: u64) -> BigInt {
+    loop {
+        let mut candidate = BigInt::from(rng.gen_biguint(bits));
+        candidate.set_bit(bits - 1, true);
+        candidate.set_bit(0, true);
+        if is_probable_prime(&candidate) {
+            return candidate;
+        }
+    }
+}
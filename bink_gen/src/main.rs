@@ -0,0 +1,215 @@
+use std::{collections::HashMap, error, fs::File, io::Write};
+
+use bink_types::keys;
+
+mod curve;
+
+const USAGE: &str = "\
+Generates BINK-compatible test curves and writes them out in the keys.json format.
+
+Usage: bink_gen [OPTIONS]
+
+Options:
+  --family <1998|2002|both>  Key family to generate BINKs for [default: both]
+  --bits <BITS>              Size of the curve's prime field in bits, at most 384 [default: 384]
+  --bink-id <HEX>            BINK ID to use, only with a single family [default: 01 or 41]
+  --product <NAME>           Product name [default: Test BINK1998 or Test BINK2002]
+  --output <FILE>            Write to a file instead of stdout
+  --help                     Print this message";
+
+/// The largest field the key hashes have room for
+const MAX_CURVE_BITS: u64 = 384;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Family {
+    Bink1998,
+    Bink2002,
+}
+
+impl Family {
+    /// The order of G is kept within the width of the signature packed into the key
+    fn order_bits(self) -> u64 {
+        match self {
+            Family::Bink1998 => 55,
+            Family::Bink2002 => 62,
+        }
+    }
+
+    fn default_bink_id(self) -> u8 {
+        match self {
+            Family::Bink1998 => 0x01,
+            Family::Bink2002 => 0x41,
+        }
+    }
+
+    /// BINK IDs below 0x40 are BINK1998, the rest BINK2002
+    fn of(bink_id: u8) -> Family {
+        if bink_id < 0x40 {
+            Family::Bink1998
+        } else {
+            Family::Bink2002
+        }
+    }
+}
+
+impl std::fmt::Display for Family {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Family::Bink1998 => write!(f, "BINK1998"),
+            Family::Bink2002 => write!(f, "BINK2002"),
+        }
+    }
+}
+
+struct Options {
+    families: Vec<Family>,
+    bits: u64,
+    bink_id: Option<u8>,
+    product: Option<String>,
+    output: Option<String>,
+}
+
+impl Options {
+    /// Returns `None` if help was requested
+    fn parse(args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = Options {
+            families: vec![Family::Bink1998, Family::Bink2002],
+            bits: MAX_CURVE_BITS,
+            bink_id: None,
+            product: None,
+            output: None,
+        };
+
+        let mut args = args;
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+            match arg.as_str() {
+                "--family" => {
+                    options.families = match value()?.as_str() {
+                        "1998" => vec![Family::Bink1998],
+                        "2002" => vec![Family::Bink2002],
+                        "both" => vec![Family::Bink1998, Family::Bink2002],
+                        family => return Err(format!("Unknown family: {family}")),
+                    }
+                }
+                "--bits" => {
+                    options.bits = value()?
+                        .parse()
+                        .map_err(|_| "--bits must be a number".to_string())?
+                }
+                "--bink-id" => {
+                    let bink_id = u8::from_str_radix(&value()?, 16)
+                        .map_err(|_| "--bink-id must be a hex byte".to_string())?;
+                    options.bink_id = Some(bink_id);
+                }
+                "--product" => options.product = Some(value()?),
+                "--output" => options.output = Some(value()?),
+                "--help" | "-h" => return Ok(None),
+                arg => return Err(format!("Unknown argument: {arg}\n\n{USAGE}")),
+            }
+        }
+
+        if options.bits > MAX_CURVE_BITS {
+            return Err(format!("--bits must be at most {MAX_CURVE_BITS}"));
+        }
+        for family in &options.families {
+            // Leave room for the cofactor
+            if options.bits < family.order_bits() + 8 {
+                return Err(format!(
+                    "--bits must be at least {} for {family}",
+                    family.order_bits() + 8
+                ));
+            }
+        }
+        if let Some(bink_id) = options.bink_id {
+            match options.families.as_slice() {
+                [family] if *family == Family::of(bink_id) => {}
+                [family] => {
+                    return Err(format!(
+                        "BINK ID {bink_id:02X} is not a {family} ID; \
+                        BINK1998 IDs are below 40 and BINK2002 IDs are 40 or above"
+                    ))
+                }
+                _ => return Err("--bink-id needs a single --family".to_string()),
+            }
+        }
+        if options.product.is_some() && options.families.len() > 1 {
+            return Err("--product needs a single --family".to_string());
+        }
+
+        Ok(Some(options))
+    }
+}
+
+fn main() -> Result<(), Box<dyn error::Error>> {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return Ok(());
+        }
+        Err(message) => {
+            eprintln!("{message}");
+            std::process::exit(2);
+        }
+    };
+
+    let mut rng = rand::thread_rng();
+    let mut products = HashMap::new();
+    let mut binks = HashMap::new();
+    for family in &options.families {
+        let bink_id = options.bink_id.unwrap_or(family.default_bink_id());
+        let bink_id = format!("{bink_id:02X}");
+        let product = options
+            .product
+            .clone()
+            .unwrap_or_else(|| format!("Test {family}"));
+
+        eprintln!("Generating {family} curve {bink_id} ({} bits)", options.bits);
+        let bink = curve::generate(&mut rng, options.bits, family.order_bits());
+
+        products.insert(
+            product,
+            keys::Product {
+                bink: vec![bink_id.clone()],
+                family: Some("Test".to_string()),
+                year: None,
+                edition: None,
+                bink_labels: HashMap::new(),
+                codes: Vec::new(),
+            },
+        );
+        binks.insert(
+            bink_id,
+            keys::Bink {
+                p: bink.curve.p.to_string(),
+                a: bink.curve.a.to_string(),
+                b: bink.curve.b.to_string(),
+                g: keys::Point {
+                    x: bink.g.0.to_string(),
+                    y: bink.g.1.to_string(),
+                },
+                public: keys::Point {
+                    x: bink.public.0.to_string(),
+                    y: bink.public.1.to_string(),
+                },
+                n: bink.n.to_string(),
+                private: Some(bink.private.to_string()),
+            },
+        );
+    }
+
+    let keys = keys::Keys {
+        products,
+        bink: binks,
+    };
+    let json = serde_json::to_string_pretty(&keys)?;
+    match options.output {
+        Some(output) => {
+            let mut f = File::create(output)?;
+            writeln!(f, "{json}")?;
+        }
+        None => println!("{json}"),
+    }
+    Ok(())
+}
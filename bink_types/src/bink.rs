@@ -1,20 +1,29 @@
 use std::collections::HashMap;
 
 use num_bigint::BigInt;
+use num_traits::Num;
 use serde::{Deserialize, Serialize};
 
+use crate::{channels::Channels, keys};
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Keys {
     #[serde(rename = "Products")]
     pub products: HashMap<String, Product>,
     #[serde(rename = "BINK")]
     pub bink: HashMap<u8, Bink>,
+    pub channels: Channels,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Product {
     #[serde(rename = "BINK")]
     pub bink: Vec<u8>,
+    pub family: Option<String>,
+    pub year: Option<u16>,
+    pub edition: Option<String>,
+    pub bink_labels: HashMap<u8, String>,
+    pub codes: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -27,7 +36,7 @@ pub struct Bink {
     pub public: Point,
     pub n: BigInt,
     #[serde(rename = "priv")]
-    pub private: BigInt,
+    pub private: Option<BigInt>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -35,3 +44,82 @@ pub struct Point {
     pub x: BigInt,
     pub y: BigInt,
 }
+
+impl Keys {
+    /// Converts the hex IDs and decimal strings of `keys.json` into bytes and BigInts
+    ///
+    /// # Panics
+    ///
+    /// Panics if an ID or number in the key data is malformed.
+    pub fn from_json(keys: &keys::Keys, channels: Channels) -> Self {
+        let products: HashMap<String, Product> = keys
+            .products
+            .iter()
+            .map(|(k, v)| {
+                let k = k.clone();
+                let v = Product {
+                    bink: v
+                        .bink
+                        .iter()
+                        .map(|v| u8::from_str_radix(v, 16).unwrap())
+                        .collect(),
+                    family: v.family.clone(),
+                    year: v.year,
+                    edition: v.edition.clone(),
+                    bink_labels: v
+                        .bink_labels
+                        .iter()
+                        .map(|(k, v)| (u8::from_str_radix(k, 16).unwrap(), v.clone()))
+                        .collect(),
+                    codes: v.codes.clone(),
+                };
+                (k, v)
+            })
+            .collect();
+
+        let bink: HashMap<u8, Bink> = keys
+            .bink
+            .iter()
+            .map(|(k, v)| {
+                let k = u8::from_str_radix(k, 16).unwrap();
+
+                let p = BigInt::from_str_radix(&v.p, 10).unwrap();
+                let a = BigInt::from_str_radix(&v.a, 10).unwrap();
+                let b = BigInt::from_str_radix(&v.b, 10).unwrap();
+                let gx = BigInt::from_str_radix(&v.g.x, 10).unwrap();
+                let gy = BigInt::from_str_radix(&v.g.y, 10).unwrap();
+                let kx = BigInt::from_str_radix(&v.public.x, 10).unwrap();
+                let ky = BigInt::from_str_radix(&v.public.y, 10).unwrap();
+                let n = BigInt::from_str_radix(&v.n, 10).unwrap();
+                let private = v
+                    .private
+                    .as_ref()
+                    .map(|private| BigInt::from_str_radix(private, 10).unwrap());
+
+                let v = Bink {
+                    p,
+                    a,
+                    b,
+                    g: Point { x: gx, y: gy },
+                    public: Point { x: kx, y: ky },
+                    n,
+                    private,
+                };
+                (k, v)
+            })
+            .collect();
+
+        Keys {
+            products,
+            bink,
+            channels,
+        }
+    }
+
+    /// Removes every private key, leaving only what's needed for validation
+    pub fn strip_private(&mut self) {
+        for bink in self.bink.values_mut() {
+            bink.private = None;
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// The distribution channel a range of channel IDs belongs to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChannelKind {
+    Retail,
+    #[serde(rename = "OEM")]
+    Oem,
+    Volume,
+    Academic,
+    Evaluation,
+}
+
+impl std::fmt::Display for ChannelKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChannelKind::Retail => write!(f, "Retail"),
+            ChannelKind::Oem => write!(f, "OEM"),
+            ChannelKind::Volume => write!(f, "Volume"),
+            ChannelKind::Academic => write!(f, "Academic"),
+            ChannelKind::Evaluation => write!(f, "Evaluation"),
+        }
+    }
+}
+
+/// An inclusive range of channel IDs with a common meaning
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChannelRange {
+    pub min: u32,
+    pub max: u32,
+    #[serde(rename = "type")]
+    pub kind: ChannelKind,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+impl ChannelRange {
+    pub fn contains(&self, channel_id: u32) -> bool {
+        (self.min..=self.max).contains(&channel_id)
+    }
+}
+
+impl std::fmt::Display for ChannelRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = self.label.clone().unwrap_or_else(|| self.kind.to_string());
+        if self.min == self.max {
+            write!(f, "{} ({:03})", label, self.min)
+        } else {
+            write!(f, "{} ({:03}-{:03})", label, self.min, self.max)
+        }
+    }
+}
+
+/// The channel catalogue read from `channels.json`
+///
+/// Products without an entry of their own fall back to the default ranges.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Channels {
+    #[serde(rename = "Default", default)]
+    pub default: Vec<ChannelRange>,
+    #[serde(rename = "Products", default)]
+    pub products: HashMap<String, Vec<ChannelRange>>,
+}
+
+impl Channels {
+    /// Returns the channel ranges that apply to the given product
+    pub fn ranges(&self, product: &str) -> &[ChannelRange] {
+        self.products
+            .get(product)
+            .map(|ranges| ranges.as_slice())
+            .unwrap_or(&self.default)
+    }
+
+    /// Looks up the kind of channel a channel ID denotes for the given product
+    pub fn kind(&self, product: &str, channel_id: u32) -> Option<ChannelKind> {
+        self.ranges(product)
+            .iter()
+            .find(|range| range.contains(channel_id))
+            .map(|range| range.kind)
+    }
+}
@@ -0,0 +1,210 @@
+//! Just enough elliptic curve and modular arithmetic to check BINKs and generate test curves. None
+//! of it is constant time, which doesn't matter for keys that have been public for decades.
+
+use num_bigint::BigInt;
+use num_traits::{One, Zero};
+
+use crate::bink;
+
+/// An affine point, or `None` for the point at infinity
+pub type Point = Option<(BigInt, BigInt)>;
+
+/// Bases for Miller-Rabin. Fixed rather than random, since there's no adversary picking the
+/// numbers tested.
+const PRIME_BASES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// The non-negative remainder of `value` divided by `modulus`
+pub fn modulo(value: &BigInt, modulus: &BigInt) -> BigInt {
+    let remainder = value % modulus;
+    if remainder < BigInt::zero() {
+        remainder + modulus
+    } else {
+        remainder
+    }
+}
+
+pub fn point(point: &bink::Point) -> Point {
+    Some((point.x.clone(), point.y.clone()))
+}
+
+/// A short Weierstrass curve y^2 = x^3 + ax + b over the prime field p
+pub struct Curve {
+    pub p: BigInt,
+    pub a: BigInt,
+    pub b: BigInt,
+}
+
+impl Curve {
+    pub fn contains(&self, point: &Point) -> bool {
+        match point {
+            Some((x, y)) => {
+                let lhs = y * y;
+                let rhs = x * x * x + &self.a * x + &self.b;
+                modulo(&(lhs - rhs), &self.p).is_zero()
+            }
+            None => true,
+        }
+    }
+
+    pub fn neg(&self, point: &Point) -> Point {
+        point
+            .as_ref()
+            .map(|(x, y)| (x.clone(), modulo(&-y, &self.p)))
+    }
+
+    pub fn add(&self, lhs: &Point, rhs: &Point) -> Point {
+        let (x1, y1) = match lhs {
+            Some(point) => point,
+            None => return rhs.clone(),
+        };
+        let (x2, y2) = match rhs {
+            Some(point) => point,
+            None => return lhs.clone(),
+        };
+        let p = &self.p;
+
+        let lambda = if x1 == x2 {
+            if modulo(&(y1 + y2), p).is_zero() {
+                return None;
+            }
+            (BigInt::from(3) * x1 * x1 + &self.a) * self.inverse(&(BigInt::from(2) * y1))
+        } else {
+            (y2 - y1) * self.inverse(&(x2 - x1))
+        };
+        let lambda = modulo(&lambda, p);
+
+        let x3 = modulo(&(&lambda * &lambda - x1 - x2), p);
+        let y3 = modulo(&(lambda * (x1 - &x3) - y1), p);
+        Some((x3, y3))
+    }
+
+    /// Double-and-add scalar multiplication, for non-negative `k`
+    pub fn mul(&self, k: &BigInt, point: &Point) -> Point {
+        let mut result = None;
+        let mut addend = point.clone();
+        for i in 0..k.bits() {
+            if k.bit(i) {
+                result = self.add(&result, &addend);
+            }
+            addend = self.add(&addend, &addend);
+        }
+        result
+    }
+
+    /// Modular inverse by Fermat's little theorem, since p is prime
+    fn inverse(&self, value: &BigInt) -> BigInt {
+        let p = &self.p;
+        modulo(value, p).modpow(&(p - BigInt::from(2)), p)
+    }
+}
+
+/// Miller-Rabin with fixed bases
+pub fn is_probable_prime(n: &BigInt) -> bool {
+    let two = BigInt::from(2);
+    if *n < two {
+        return false;
+    }
+    for base in PRIME_BASES {
+        let base = BigInt::from(base);
+        if *n == base {
+            return true;
+        }
+        if (n % &base).is_zero() {
+            return false;
+        }
+    }
+
+    let n_minus_one: BigInt = n - 1;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
+    'bases: for base in PRIME_BASES {
+        let mut x = BigInt::from(base).modpow(&d, n);
+        if x.is_one() || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'bases;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// y^2 = x^3 + 2x + 2 over 17, whose point (5, 1) has order 19
+    fn textbook() -> (Curve, Point) {
+        let curve = Curve {
+            p: BigInt::from(17),
+            a: BigInt::from(2),
+            b: BigInt::from(2),
+        };
+        (curve, Some((BigInt::from(5), BigInt::from(1))))
+    }
+
+    fn affine(x: i32, y: i32) -> Point {
+        Some((BigInt::from(x), BigInt::from(y)))
+    }
+
+    #[test]
+    fn adds_and_multiplies_points() {
+        let (curve, g) = textbook();
+        assert!(curve.contains(&g));
+        assert!(!curve.contains(&affine(5, 2)));
+        assert_eq!(curve.add(&g, &g), affine(6, 3));
+        assert_eq!(curve.mul(&BigInt::from(2), &g), affine(6, 3));
+        assert_eq!(curve.mul(&BigInt::from(3), &g), affine(10, 6));
+        assert_eq!(curve.mul(&BigInt::from(18), &g), curve.neg(&g));
+        assert_eq!(curve.mul(&BigInt::from(19), &g), None);
+        assert_eq!(curve.add(&g, &curve.neg(&g)), None);
+        assert_eq!(curve.add(&None, &g), g);
+        assert_eq!(curve.mul(&BigInt::zero(), &g), None);
+        for k in 1..19 {
+            assert!(curve.contains(&curve.mul(&BigInt::from(k), &g)), "{k}G");
+        }
+    }
+
+    #[test]
+    fn modulo_is_never_negative() {
+        assert_eq!(
+            modulo(&BigInt::from(-3), &BigInt::from(17)),
+            BigInt::from(14)
+        );
+        assert_eq!(
+            modulo(&BigInt::from(20), &BigInt::from(17)),
+            BigInt::from(3)
+        );
+    }
+
+    #[test]
+    fn tells_primes_from_composites() {
+        for prime in [
+            2_u64,
+            3,
+            37,
+            41,
+            65537,
+            2_147_483_647,
+            14_828_956_481_366_719,
+        ] {
+            assert!(is_probable_prime(&BigInt::from(prime)), "{prime}");
+        }
+        // 561 is a Carmichael number and 3215031751 a strong pseudoprime to bases 2, 3, 5 and 7
+        for composite in [
+            0_u64,
+            1,
+            4,
+            561,
+            1_373_653,
+            3_215_031_751,
+            14_828_956_556_373_045,
+        ] {
+            assert!(!is_probable_prime(&BigInt::from(composite)), "{composite}");
+        }
+    }
+}
@@ -14,6 +14,17 @@ pub struct Keys {
 pub struct Product {
     #[serde(rename = "BINK")]
     pub bink: Vec<String>,
+    #[serde(rename = "Family", default, skip_serializing_if = "Option::is_none")]
+    pub family: Option<String>,
+    #[serde(rename = "Year", default, skip_serializing_if = "Option::is_none")]
+    pub year: Option<u16>,
+    #[serde(rename = "Edition", default, skip_serializing_if = "Option::is_none")]
+    pub edition: Option<String>,
+    /// Labels such as "Retail" or "OEM", keyed by BINK ID
+    #[serde(rename = "BINKLabels", default, skip_serializing_if = "HashMap::is_empty")]
+    pub bink_labels: HashMap<String, String>,
+    #[serde(rename = "Codes", default, skip_serializing_if = "Vec::is_empty")]
+    pub codes: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -25,8 +36,9 @@ pub struct Bink {
     #[serde(rename = "pub")]
     pub public: Point,
     pub n: String,
-    #[serde(rename = "priv")]
-    pub private: String,
+    /// Missing from datasets that are only used for validation
+    #[serde(rename = "priv", default, skip_serializing_if = "Option::is_none")]
+    pub private: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
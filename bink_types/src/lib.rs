@@ -1,2 +1,4 @@
 pub mod bink;
+pub mod channels;
+pub mod curve;
 pub mod keys;
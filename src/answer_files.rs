@@ -0,0 +1,505 @@
+use serde::{Deserialize, Serialize};
+
+pub const STORAGE_KEY: &str = "mskey-web.answerfile";
+
+/// The kinds of answer file a key can be written into
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnswerTemplate {
+    #[default]
+    WinntSif,
+    UnattendTxt,
+    SysprepInf,
+    OfficeSetupIni,
+}
+
+impl AnswerTemplate {
+    pub const ALL: [AnswerTemplate; 4] = [
+        AnswerTemplate::WinntSif,
+        AnswerTemplate::UnattendTxt,
+        AnswerTemplate::SysprepInf,
+        AnswerTemplate::OfficeSetupIni,
+    ];
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|template| template.id() == id)
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            AnswerTemplate::WinntSif => "winntsif",
+            AnswerTemplate::UnattendTxt => "unattendtxt",
+            AnswerTemplate::SysprepInf => "sysprepinf",
+            AnswerTemplate::OfficeSetupIni => "setupini",
+        }
+    }
+
+    pub fn filename(self) -> &'static str {
+        match self {
+            AnswerTemplate::WinntSif => "winnt.sif",
+            AnswerTemplate::UnattendTxt => "unattend.txt",
+            AnswerTemplate::SysprepInf => "sysprep.inf",
+            AnswerTemplate::OfficeSetupIni => "setup.ini",
+        }
+    }
+
+    /// The file name for one of many keys, e.g. `winnt-0042.sif`
+    pub fn numbered_filename(self, number: usize, width: usize) -> String {
+        let (stem, extension) = self.filename().split_once('.').unwrap_or_default();
+        format!("{stem}-{number:0width$}.{extension}")
+    }
+
+    /// Office's setup.ini has nothing to do with the Windows key field
+    pub fn uses_key_field(self) -> bool {
+        self != AnswerTemplate::OfficeSetupIni
+    }
+}
+
+impl std::fmt::Display for AnswerTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnswerTemplate::WinntSif => write!(f, "winnt.sif (CD-based unattended install)"),
+            AnswerTemplate::UnattendTxt => write!(f, "unattend.txt (winnt32 /unattend)"),
+            AnswerTemplate::SysprepInf => write!(f, "sysprep.inf (Sysprep mini-setup)"),
+            AnswerTemplate::OfficeSetupIni => write!(f, "setup.ini (Office PIDKEY)"),
+        }
+    }
+}
+
+/// Which entry in `[UserData]` holds the key
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyField {
+    /// Windows XP and Server 2003
+    #[default]
+    ProductKey,
+    /// Windows 2000
+    ProductId,
+}
+
+impl KeyField {
+    pub const ALL: [KeyField; 2] = [KeyField::ProductKey, KeyField::ProductId];
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|field| field.id() == id)
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            KeyField::ProductKey => "ProductKey",
+            KeyField::ProductId => "ProductID",
+        }
+    }
+}
+
+impl std::fmt::Display for KeyField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyField::ProductKey => write!(f, "ProductKey (Windows XP, Server 2003)"),
+            KeyField::ProductId => write!(f, "ProductID (Windows 2000)"),
+        }
+    }
+}
+
+/// Everything filled in on an answer file apart from the key
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnswerFile {
+    pub template: AnswerTemplate,
+    pub key_field: KeyField,
+    pub full_name: String,
+    pub org_name: String,
+    /// Left blank to let Setup make up a name
+    pub computer_name: String,
+}
+
+/// Quotes a value for an answer file, which has no way of escaping a quote inside one
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', ""))
+}
+
+impl AnswerFile {
+    /// Writes out the answer file for a key, with Windows line endings. `number` tells the
+    /// computers apart when there's a file per key.
+    pub fn render(&self, key: &str, number: Option<usize>) -> String {
+        let computer_name = match (self.computer_name.trim(), number) {
+            ("", _) | ("*", _) => "*".to_string(),
+            (name, Some(number)) => quote(&format!("{name}-{number}")),
+            (name, None) => quote(name),
+        };
+        let user_data = [
+            format!("{}={}", self.key_field.id(), quote(key)),
+            format!("FullName={}", quote(self.full_name.trim())),
+            format!("OrgName={}", quote(self.org_name.trim())),
+            format!("ComputerName={computer_name}"),
+        ];
+
+        let sections: Vec<(&str, Vec<String>)> = match self.template {
+            AnswerTemplate::WinntSif => vec![
+                (
+                    "Data",
+                    lines(&[
+                        "AutoPartition=1",
+                        "MsDosInitiated=\"0\"",
+                        "UnattendedInstall=\"Yes\"",
+                    ]),
+                ),
+                (
+                    "Unattended",
+                    lines(&[
+                        "UnattendMode=FullUnattended",
+                        "OemSkipEula=Yes",
+                        "OemPreinstall=No",
+                        "TargetPath=\\WINDOWS",
+                    ]),
+                ),
+                (
+                    "GuiUnattended",
+                    lines(&["OemSkipWelcome=1", "OemSkipRegional=1"]),
+                ),
+                ("UserData", user_data.to_vec()),
+            ],
+            AnswerTemplate::UnattendTxt => vec![
+                (
+                    "Unattended",
+                    lines(&[
+                        "UnattendMode=FullUnattended",
+                        "OemSkipEula=Yes",
+                        "OemPreinstall=No",
+                        "TargetPath=\\WINDOWS",
+                    ]),
+                ),
+                (
+                    "GuiUnattended",
+                    lines(&["OemSkipWelcome=1", "OemSkipRegional=1"]),
+                ),
+                ("UserData", user_data.to_vec()),
+            ],
+            AnswerTemplate::SysprepInf => vec![
+                (
+                    "Unattended",
+                    lines(&["OemSkipEula=Yes", "InstallFilesPath=C:\\sysprep\\i386"]),
+                ),
+                (
+                    "GuiUnattended",
+                    lines(&["OemSkipWelcome=1", "OemSkipRegional=1"]),
+                ),
+                ("UserData", user_data.to_vec()),
+            ],
+            // Office wants the key without dashes and doesn't quote anything
+            AnswerTemplate::OfficeSetupIni => vec![(
+                "Options",
+                vec![
+                    format!("PIDKEY={}", key.replace('-', "")),
+                    format!("USERNAME={}", self.full_name.trim()),
+                    format!("COMPANYNAME={}", self.org_name.trim()),
+                ],
+            )],
+        };
+
+        let indent = if self.template == AnswerTemplate::OfficeSetupIni {
+            ""
+        } else {
+            "    "
+        };
+        sections
+            .into_iter()
+            .map(|(name, entries)| {
+                let entries = entries
+                    .into_iter()
+                    .map(|entry| format!("{indent}{entry}\r\n"))
+                    .collect::<String>();
+                format!("[{name}]\r\n{entries}")
+            })
+            .collect::<Vec<_>>()
+            .join("\r\n")
+    }
+}
+
+fn lines(entries: &[&str]) -> Vec<String> {
+    entries.iter().map(|entry| entry.to_string()).collect()
+}
+
+/// The entries that hold a product key, in Windows and Office answer files
+const KEY_FIELDS: [&str; 3] = ["ProductKey", "ProductID", "PIDKEY"];
+
+/// A product key entry found in an answer file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FoundKey {
+    /// Counting from 1
+    pub line: usize,
+    pub field: String,
+    pub key: String,
+}
+
+/// Puts the dashes back into a key written without them, as Office's `PIDKEY` is
+fn dashed(key: &str) -> String {
+    let key = key.replace(' ', "").to_uppercase();
+    if key.len() != 25 || !key.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return key;
+    }
+    key.as_bytes()
+        .chunks(5)
+        .map(|group| String::from_utf8_lossy(group))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Finds the product key entries in an answer file, in any of the formats it can write
+pub fn find_keys(text: &str) -> Vec<FoundKey> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let (name, value) = line.trim().split_once('=')?;
+            let field = KEY_FIELDS
+                .into_iter()
+                .find(|field| field.eq_ignore_ascii_case(name.trim()))?;
+            let value = value.trim();
+            // A quoted value ends at its closing quote, anything else at a comment
+            let value = match value.strip_prefix('"') {
+                Some(quoted) => quoted.split('"').next().unwrap_or_default(),
+                None => value.split(';').next().unwrap_or_default(),
+            };
+            let key = dashed(value.trim());
+            (!key.is_empty()).then(|| FoundKey {
+                line: i + 1,
+                field: field.to_string(),
+                key,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "BCDFG-HJKMP-QRTVW-XY234-6789B";
+
+    fn found(line: usize, field: &str, key: &str) -> FoundKey {
+        FoundKey {
+            line,
+            field: field.to_string(),
+            key: key.to_string(),
+        }
+    }
+
+    #[test]
+    fn finds_keys_in_each_field() {
+        let dashless = KEY.replace('-', "");
+        let text = format!(
+            "[UserData]\r\n    ProductKey=\"{KEY}\"\r\n    ProductID={KEY}\r\n\
+             [Options]\r\nPIDKEY={dashless}\r\n"
+        );
+        assert_eq!(
+            find_keys(&text),
+            [
+                found(2, "ProductKey", KEY),
+                found(3, "ProductID", KEY),
+                found(5, "PIDKEY", KEY),
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_field_names_in_any_case() {
+        let text = format!("productkey = {KEY}\nPRODUCTID={KEY}\npidKey={KEY}");
+        let fields = find_keys(&text)
+            .into_iter()
+            .map(|found| found.field)
+            .collect::<Vec<_>>();
+        // The field is reported as it's usually written
+        assert_eq!(fields, ["ProductKey", "ProductID", "PIDKEY"]);
+        // Only whole field names count
+        assert!(find_keys(&format!("ProductKeys={KEY}\nMyPIDKEY={KEY}")).is_empty());
+    }
+
+    #[test]
+    fn reads_quoted_values() {
+        assert_eq!(
+            find_keys(&format!("ProductKey = \"{KEY}\" ; from the sticker")),
+            [found(1, "ProductKey", KEY)]
+        );
+        // A semicolon inside quotes is part of the value, not a comment
+        assert_eq!(find_keys("ProductKey=\"ABC;DEF\"")[0].key, "ABC;DEF");
+        // An empty value isn't a key
+        assert!(find_keys("ProductKey=\"\"\nProductID=").is_empty());
+    }
+
+    #[test]
+    fn skips_comments() {
+        assert_eq!(
+            find_keys(&format!("ProductKey={KEY} ; the old key was here")),
+            [found(1, "ProductKey", KEY)]
+        );
+        // Commented-out entries aren't keys
+        assert!(find_keys(&format!("; ProductKey={KEY}\n;PIDKEY={KEY}")).is_empty());
+    }
+
+    #[test]
+    fn puts_dashes_back_into_keys() {
+        let dashless = KEY.replace('-', "");
+        assert_eq!(find_keys(&format!("PIDKEY={dashless}"))[0].key, KEY);
+        assert_eq!(
+            find_keys(&format!("PIDKEY={}", dashless.to_lowercase()))[0].key,
+            KEY
+        );
+        assert_eq!(
+            find_keys("PIDKEY=BCDFG HJKMP QRTVW XY234 6789B")[0].key,
+            KEY
+        );
+        // Anything that isn't 25 characters is left as it is, to be reported as invalid
+        assert_eq!(find_keys("PIDKEY=BCDFGHJKMP")[0].key, "BCDFGHJKMP");
+    }
+
+    #[test]
+    fn counts_lines_from_one() {
+        let text = format!("\n\n[UserData]\n\n    ProductKey={KEY}\n\n    ProductID={KEY}\n");
+        let lines = find_keys(&text)
+            .into_iter()
+            .map(|found| found.line)
+            .collect::<Vec<_>>();
+        assert_eq!(lines, [5, 7]);
+    }
+
+    fn answer_file(template: AnswerTemplate) -> AnswerFile {
+        AnswerFile {
+            template,
+            key_field: KeyField::ProductKey,
+            full_name: " Jane Doe ".to_string(),
+            org_name: "Contoso".to_string(),
+            computer_name: "DESK".to_string(),
+        }
+    }
+
+    const USER_DATA: &str = concat!(
+        "[UserData]\r\n",
+        "    ProductKey=\"BCDFG-HJKMP-QRTVW-XY234-6789B\"\r\n",
+        "    FullName=\"Jane Doe\"\r\n",
+        "    OrgName=\"Contoso\"\r\n",
+        "    ComputerName=\"DESK\"\r\n",
+    );
+
+    #[test]
+    fn renders_winnt_sif() {
+        let expected = [
+            "[Data]\r\n",
+            "    AutoPartition=1\r\n",
+            "    MsDosInitiated=\"0\"\r\n",
+            "    UnattendedInstall=\"Yes\"\r\n",
+            "\r\n",
+            "[Unattended]\r\n",
+            "    UnattendMode=FullUnattended\r\n",
+            "    OemSkipEula=Yes\r\n",
+            "    OemPreinstall=No\r\n",
+            "    TargetPath=\\WINDOWS\r\n",
+            "\r\n",
+            "[GuiUnattended]\r\n",
+            "    OemSkipWelcome=1\r\n",
+            "    OemSkipRegional=1\r\n",
+            "\r\n",
+            USER_DATA,
+        ]
+        .concat();
+        assert_eq!(
+            answer_file(AnswerTemplate::WinntSif).render(KEY, None),
+            expected
+        );
+    }
+
+    #[test]
+    fn renders_unattend_txt() {
+        let expected = [
+            "[Unattended]\r\n",
+            "    UnattendMode=FullUnattended\r\n",
+            "    OemSkipEula=Yes\r\n",
+            "    OemPreinstall=No\r\n",
+            "    TargetPath=\\WINDOWS\r\n",
+            "\r\n",
+            "[GuiUnattended]\r\n",
+            "    OemSkipWelcome=1\r\n",
+            "    OemSkipRegional=1\r\n",
+            "\r\n",
+            USER_DATA,
+        ]
+        .concat();
+        assert_eq!(
+            answer_file(AnswerTemplate::UnattendTxt).render(KEY, None),
+            expected
+        );
+    }
+
+    #[test]
+    fn renders_sysprep_inf() {
+        let expected = [
+            "[Unattended]\r\n",
+            "    OemSkipEula=Yes\r\n",
+            "    InstallFilesPath=C:\\sysprep\\i386\r\n",
+            "\r\n",
+            "[GuiUnattended]\r\n",
+            "    OemSkipWelcome=1\r\n",
+            "    OemSkipRegional=1\r\n",
+            "\r\n",
+            USER_DATA,
+        ]
+        .concat();
+        assert_eq!(
+            answer_file(AnswerTemplate::SysprepInf).render(KEY, None),
+            expected
+        );
+    }
+
+    #[test]
+    fn renders_office_setup_ini() {
+        let mut file = answer_file(AnswerTemplate::OfficeSetupIni);
+        // Office has no field to choose, so this makes no difference
+        file.key_field = KeyField::ProductId;
+        assert_eq!(
+            file.render(KEY, Some(3)),
+            concat!(
+                "[Options]\r\n",
+                "PIDKEY=BCDFGHJKMPQRTVWXY2346789B\r\n",
+                "USERNAME=Jane Doe\r\n",
+                "COMPANYNAME=Contoso\r\n",
+            )
+        );
+    }
+
+    #[test]
+    fn fills_in_the_user_data() {
+        let mut file = answer_file(AnswerTemplate::SysprepInf);
+        file.key_field = KeyField::ProductId;
+        file.org_name = "The \"Best\" Org".to_string();
+        let rendered = file.render(KEY, Some(7));
+        assert!(rendered.contains(&format!("    ProductID=\"{KEY}\"\r\n")));
+        // Quotes can't be escaped, so they're dropped
+        assert!(rendered.contains("    OrgName=\"The Best Org\"\r\n"));
+        // Each numbered file gets its own computer name
+        assert!(rendered.contains("    ComputerName=\"DESK-7\"\r\n"));
+
+        // Setup makes up a name when there isn't one
+        for name in ["", " ", "*"] {
+            file.computer_name = name.to_string();
+            assert!(file.render(KEY, Some(7)).contains("    ComputerName=*\r\n"));
+        }
+    }
+
+    #[test]
+    fn numbers_filenames() {
+        assert_eq!(
+            AnswerTemplate::WinntSif.numbered_filename(42, 4),
+            "winnt-0042.sif"
+        );
+        assert_eq!(
+            AnswerTemplate::OfficeSetupIni.numbered_filename(123, 2),
+            "setup-123.ini"
+        );
+    }
+
+    #[test]
+    fn finds_the_key_in_rendered_files() {
+        for template in AnswerTemplate::ALL {
+            let found = find_keys(&answer_file(template).render(KEY, None));
+            assert_eq!(found.len(), 1, "{template}");
+            assert_eq!(found[0].key, KEY, "{template}");
+        }
+    }
+}
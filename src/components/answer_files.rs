@@ -0,0 +1,185 @@
+use leptos::*;
+
+use crate::{
+    answer_files::{self, AnswerFile, AnswerTemplate, KeyField},
+    components::{
+        button::Button,
+        fields::{SelectField, TextField},
+    },
+    files, storage,
+};
+
+/// Writes the generated key into an unattended-install answer file, or a whole bulk job's keys
+/// into one file each. `on_download` is called with the key when its answer file is downloaded.
+#[component]
+pub fn AnswerFiles(
+    #[prop(into)] key: Signal<Option<String>>,
+    #[prop(into)] bulk_keys: Signal<Vec<String>>,
+    on_download: Callback<String>,
+) -> impl IntoView {
+    let initial = storage::load::<AnswerFile>(answer_files::STORAGE_KEY).unwrap_or_default();
+    let (template, set_template) = create_signal(initial.template);
+    let (key_field, set_key_field) = create_signal(initial.key_field);
+    let (full_name, set_full_name) = create_signal(initial.full_name);
+    let (org_name, set_org_name) = create_signal(initial.org_name);
+    let (computer_name, set_computer_name) = create_signal(initial.computer_name);
+
+    let answer_file = create_memo(move |_| AnswerFile {
+        template: template.get(),
+        key_field: key_field.get(),
+        full_name: full_name.get(),
+        org_name: org_name.get(),
+        computer_name: computer_name.get(),
+    });
+    create_effect(move |_| {
+        answer_file.with(|answer_file| storage::save(answer_files::STORAGE_KEY, answer_file))
+    });
+
+    let update_template = move |ev| {
+        if let Some(template) = AnswerTemplate::from_id(&event_target_value(&ev)) {
+            set_template.set(template);
+        }
+    };
+
+    let update_key_field = move |ev| {
+        if let Some(key_field) = KeyField::from_id(&event_target_value(&ev)) {
+            set_key_field.set(key_field);
+        }
+    };
+
+    let preview = move || {
+        key.get()
+            .filter(|key| !key.is_empty())
+            .map(|key| answer_file.get().render(&key, None))
+            .unwrap_or_default()
+    };
+
+    let download = move |_| {
+        let answer_file = answer_file.get();
+        if let Some(key) = key.get().filter(|key| !key.is_empty()) {
+            files::download(
+                answer_file.template.filename(),
+                "text/plain",
+                &answer_file.render(&key, None),
+            );
+            on_download.call(key);
+        }
+    };
+
+    let download_bulk = move |_| {
+        let answer_file = answer_file.get();
+        let keys = bulk_keys.get();
+        let width = keys.len().to_string().len();
+        let contents = keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| {
+                (
+                    answer_file.template.numbered_filename(i + 1, width),
+                    answer_file.render(key, Some(i + 1)),
+                )
+            })
+            .collect::<Vec<_>>();
+        files::download_bytes(
+            "answer-files.zip",
+            "application/zip",
+            &files::zip(&contents),
+        );
+    };
+
+    view! {
+        <div class="flex flex-col sm:flex-row gap-4 mb-4">
+            <div class="flex-1">
+                <SelectField label="Template" id="answertemplate" on_input=update_template>
+                    {move || {
+                        let selected = template.get();
+                        AnswerTemplate::ALL
+                            .into_iter()
+                            .map(|template| {
+                                view! {
+                                    <option value=template.id() selected=template == selected>
+                                        {template.to_string()}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    }}
+                </SelectField>
+            </div>
+            <Show when=move || template.get().uses_key_field()>
+                <div class="flex-1">
+                    <SelectField label="Key Field" id="answerkeyfield" on_input=update_key_field>
+                        {move || {
+                            let selected = key_field.get();
+                            KeyField::ALL
+                                .into_iter()
+                                .map(|field| {
+                                    view! {
+                                        <option value=field.id() selected=field == selected>
+                                            {field.to_string()}
+                                        </option>
+                                    }
+                                })
+                                .collect::<Vec<_>>()
+                        }}
+                    </SelectField>
+                </div>
+            </Show>
+        </div>
+        <div class="flex flex-col sm:flex-row gap-4 mb-4">
+            <div class="flex-1">
+                <TextField
+                    label="Full Name"
+                    id="answerfullname"
+                    on_change=|_| ()
+                    on_input=move |ev| set_full_name.set(event_target_value(&ev))
+                    value=full_name
+                />
+            </div>
+            <div class="flex-1">
+                <TextField
+                    label="Organization"
+                    id="answerorgname"
+                    on_change=|_| ()
+                    on_input=move |ev| set_org_name.set(event_target_value(&ev))
+                    value=org_name
+                />
+            </div>
+            <Show when=move || template.get().uses_key_field()>
+                <div class="flex-1">
+                    <TextField
+                        label="Computer Name"
+                        id="answercomputername"
+                        on_change=|_| ()
+                        on_input=move |ev| set_computer_name.set(event_target_value(&ev))
+                        value=computer_name
+                    />
+                </div>
+            </Show>
+        </div>
+        <textarea
+            class="block w-full h-48 mb-4 px-3 py-2 rounded-lg font-mono bg-slate-100 dark:bg-slate-900"
+            readonly
+            prop:value=preview
+        ></textarea>
+        <div class="flex flex-row flex-wrap gap-4">
+            <Button
+                class="text-slate-100 bg-emerald-500 hover:bg-emerald-600 dark:bg-emerald-600 dark:hover:bg-emerald-700"
+                on_click=download
+            >
+                {move || format!("Download {}", template.get().filename())}
+            </Button>
+            <Show when=move || bulk_keys.with(|keys| !keys.is_empty())>
+                <Button
+                    class="bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600"
+                    on_click=download_bulk
+                >
+                    {move || format!("Download {} Bulk Keys as ZIP", bulk_keys.with(Vec::len))}
+                </Button>
+            </Show>
+        </div>
+        <p class="mt-4 text-sm text-slate-500 dark:text-slate-400">
+            "Leave the computer name blank to let Setup pick one. In the ZIP, each file's computer name gets the file's number added to the end."
+        </p>
+    }
+}
@@ -1,11 +1,22 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
+use bink_types::channels::ChannelKind;
 use leptos::*;
 
-use crate::{components::fields::Label, crypto::ProductKey2002};
+use crate::{
+    components::{fields::Label, key_editor::KeyEdits, number_cells::NumberCells},
+    crypto::ProductKey2002,
+};
 
 #[component]
-pub fn Bink2002Details(key: ProductKey2002, bink_ids: HashMap<String, u8>) -> impl IntoView {
+pub fn Bink2002Details(
+    key: ProductKey2002,
+    bink_ids: HashMap<String, u8>,
+    channel_kinds: HashMap<String, ChannelKind>,
+    /// Adds an Edit column with inputs for the fields a new key can be signed with
+    #[prop(optional_no_strip)]
+    edits: Option<KeyEdits>,
+) -> impl IntoView {
     let bink_id_list = {
         let bink_id_list = bink_ids
             .iter()
@@ -16,6 +27,17 @@ pub fn Bink2002Details(key: ProductKey2002, bink_ids: HashMap<String, u8>) -> im
             .collect::<Vec<_>>();
         bink_id_list
     };
+    let channel_type = {
+        let kinds = channel_kinds
+            .values()
+            .map(|kind| kind.to_string())
+            .collect::<BTreeSet<_>>();
+        if kinds.is_empty() {
+            "Unknown".to_string()
+        } else {
+            kinds.into_iter().collect::<Vec<_>>().join(" / ")
+        }
+    };
     view! {
         <div class="mb-4">
             <Label>"Products"</Label>
@@ -24,51 +46,51 @@ pub fn Bink2002Details(key: ProductKey2002, bink_ids: HashMap<String, u8>) -> im
         <div class="mb-4">
             <Label>"Details"</Label>
             <table class="w-full text-left">
+                {edits
+                    .map(|_| {
+                        view! {
+                            <thead>
+                                <tr>
+                                    <th></th>
+                                    <th></th>
+                                    <th></th>
+                                    <th class="font-semibold px-4 py-2">"Edit"</th>
+                                </tr>
+                            </thead>
+                        }
+                    })}
                 <tbody>
                     <tr>
                         <th class="font-semibold px-4 py-2">"Upgrade"</th>
                         <td class="font-mono px-4 py-2">{key.upgrade}</td>
                         <td></td>
+                        {KeyEdits::cell(edits, "Upgrade")}
                     </tr>
                     <tr class="bg-slate-100 dark:bg-slate-700">
                         <th class="font-semibold px-4 py-2">"Channel ID"</th>
-                        <td class="font-mono px-4 py-2">{key.channel_id.clone()}</td>
-                        <td class="font-mono px-4 py-2">
-                            {
-                                let channel_id = key.channel_id.parse::<u32>().unwrap();
-                                format!("0x{:X}", channel_id)
-                            }
-                        </td>
+                        <NumberCells value=key.channel_id.clone() />
+                        {KeyEdits::cell(edits, "Channel ID")}
                     </tr>
                     <tr>
-                        <th class="font-semibold px-4 py-2">"Hash"</th>
-                        <td class="font-mono px-4 py-2">{key.hash.clone()}</td>
-                        <td class="font-mono px-4 py-2">
-                            {
-                                let hash = key.hash.parse::<u32>().unwrap();
-                                format!("0x{:X}", hash)
-                            }
-                        </td>
+                        <th class="font-semibold px-4 py-2">"Channel Type"</th>
+                        <td class="px-4 py-2">{channel_type}</td>
+                        <td></td>
+                        {KeyEdits::cell(edits, "Channel Type")}
                     </tr>
                     <tr class="bg-slate-100 dark:bg-slate-700">
-                        <th class="font-semibold px-4 py-2">"Signature"</th>
-                        <td class="font-mono px-4 py-2">{key.signature.clone()}</td>
-                        <td class="font-mono px-4 py-2">
-                            {
-                                let signature = key.signature.parse::<u64>().unwrap();
-                                format!("0x{:X}", signature)
-                            }
-                        </td>
+                        <th class="font-semibold px-4 py-2">"Hash"</th>
+                        <NumberCells value=key.hash.clone() />
+                        {KeyEdits::cell(edits, "Hash")}
                     </tr>
                     <tr>
+                        <th class="font-semibold px-4 py-2">"Signature"</th>
+                        <NumberCells value=key.signature.clone() />
+                        {KeyEdits::cell(edits, "Signature")}
+                    </tr>
+                    <tr class="bg-slate-100 dark:bg-slate-700">
                         <th class="font-semibold px-4 py-2">"Auth Info"</th>
-                        <td class="font-mono px-4 py-2">{key.auth_info.clone()}</td>
-                        <td class="font-mono px-4 py-2">
-                            {
-                                let auth_info = key.auth_info.parse::<u32>().unwrap();
-                                format!("0x{:X}", auth_info)
-                            }
-                        </td>
+                        <NumberCells value=key.auth_info.clone() />
+                        {KeyEdits::cell(edits, "Auth Info")}
                     </tr>
                 </tbody>
             </table>
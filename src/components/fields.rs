@@ -54,7 +54,7 @@ where
             id=id
             class=format!("{INPUT_CLASSES} no-spinner")
             on:input=on_input
-            prop:value=value.get()
+            prop:value=value
             placeholder=placeholder
         />
     }
@@ -81,7 +81,7 @@ where
             class=INPUT_CLASSES
             on:change=on_change
             on:input=on_input
-            prop:value=value.get()
+            prop:value=value
         />
     }
 }
@@ -105,7 +105,7 @@ where
                 id=id
                 class="mr-2 sr-only peer"
                 on:change=on_change
-                prop:checked=checked.get()
+                prop:checked=checked
             />
             <div class="relative w-11 h-6 bg-gray-200
             peer-focus:outline-none
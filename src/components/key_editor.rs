@@ -0,0 +1,326 @@
+use std::{collections::BTreeSet, rc::Rc};
+
+use leptos::*;
+
+use crate::{
+    components::{button::Button, error::Error, fields::Label, loading::Loading},
+    crypto::{KeyTool, ProductKey, MAX_AUTH_INFO},
+    worker::{self, GenKeyRequest},
+};
+
+const CELL_INPUT_CLASSES: &str =
+    "w-32 px-2 py-1 rounded-lg font-mono bg-white dark:bg-slate-900 no-spinner";
+
+/// The decoded fields of a key, as shown side by side in the editor
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct KeyFields {
+    upgrade: bool,
+    channel_id: String,
+    /// BINK1998 only
+    sequence: Option<String>,
+    /// BINK2002 only
+    auth_info: Option<String>,
+    hash: String,
+    signature: String,
+}
+
+impl KeyFields {
+    fn from_key(key: &ProductKey) -> Option<Self> {
+        match key {
+            ProductKey::Bink1998 { key, .. } => Some(KeyFields {
+                upgrade: key.upgrade,
+                channel_id: key.channel_id.clone(),
+                sequence: Some(key.sequence.clone()),
+                auth_info: None,
+                hash: key.hash.clone(),
+                signature: key.signature.clone(),
+            }),
+            ProductKey::Bink2002 { key, .. } => Some(KeyFields {
+                upgrade: key.upgrade,
+                channel_id: key.channel_id.clone(),
+                sequence: None,
+                auth_info: Some(key.auth_info.clone()),
+                hash: key.hash.clone(),
+                signature: key.signature.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Each field's name and value, in the order they're listed
+    fn rows(&self) -> Vec<(&'static str, String)> {
+        let mut rows = vec![
+            ("Upgrade", self.upgrade.to_string()),
+            ("Channel ID", self.channel_id.clone()),
+        ];
+        if let Some(sequence) = &self.sequence {
+            rows.push(("Sequence", sequence.clone()));
+        }
+        if let Some(auth_info) = &self.auth_info {
+            rows.push(("Auth Info", auth_info.clone()));
+        }
+        rows.push(("Hash", self.hash.clone()));
+        rows.push(("Signature", self.signature.clone()));
+        rows
+    }
+}
+
+/// The fields of a key being edited. The inputs for them go in the Edit column of the key's
+/// details table, and `KeyEditor` signs a new key from them.
+#[derive(Clone, Copy)]
+pub struct KeyEdits {
+    upgrade: RwSignal<bool>,
+    channel_id: RwSignal<String>,
+    sequence: RwSignal<String>,
+    auth_info: RwSignal<String>,
+}
+
+impl KeyEdits {
+    /// Starts from the fields of a validated BINK1998 or BINK2002 key
+    pub fn new(key: &ProductKey) -> Option<Self> {
+        let fields = KeyFields::from_key(key)?;
+        Some(KeyEdits {
+            upgrade: create_rw_signal(fields.upgrade),
+            channel_id: create_rw_signal(fields.channel_id),
+            sequence: create_rw_signal(fields.sequence.unwrap_or_default()),
+            auth_info: create_rw_signal(fields.auth_info.unwrap_or_default()),
+        })
+    }
+
+    fn request(self, bink_id: u8) -> GenKeyRequest {
+        GenKeyRequest {
+            bink_id,
+            channel_id: self.channel_id.get(),
+            sequence: self.sequence.get(),
+            auth_info: self.auth_info.get(),
+            upgrade: self.upgrade.get(),
+        }
+    }
+
+    /// The Edit cell for a row of a details table, if the table is being edited. Fields that
+    /// can't be changed get an empty cell.
+    pub fn cell(edits: Option<Self>, field: &'static str) -> Option<View> {
+        let edits = edits?;
+        let input = match field {
+            "Upgrade" => view! {
+                <input
+                    type="checkbox"
+                    class="w-4 h-4 cursor-pointer"
+                    prop:checked=edits.upgrade
+                    on:change=move |ev| edits.upgrade.set(event_target_checked(&ev))
+                />
+            }
+            .into_view(),
+            "Channel ID" => view! {
+                <input
+                    type="number"
+                    min="0"
+                    max="999"
+                    class=CELL_INPUT_CLASSES
+                    prop:value=edits.channel_id
+                    on:input=move |ev| edits.channel_id.set(event_target_value(&ev))
+                />
+            }
+            .into_view(),
+            "Sequence" => view! {
+                <input
+                    type="number"
+                    min="0"
+                    max="999999"
+                    placeholder="Random"
+                    class=CELL_INPUT_CLASSES
+                    prop:value=edits.sequence
+                    on:input=move |ev| edits.sequence.set(event_target_value(&ev))
+                />
+            }
+            .into_view(),
+            "Auth Info" => view! {
+                <input
+                    type="number"
+                    min="0"
+                    max=MAX_AUTH_INFO.to_string()
+                    placeholder="Random"
+                    class=CELL_INPUT_CLASSES
+                    prop:value=edits.auth_info
+                    on:input=move |ev| edits.auth_info.set(event_target_value(&ev))
+                />
+            }
+            .into_view(),
+            _ => ().into_view(),
+        };
+        Some(view! { <td class="px-4 py-2">{input}</td> }.into_view())
+    }
+}
+
+/// Signs a new key with the same BINK from the fields in `edits`, showing the old and new fields
+/// next to each other
+#[component]
+pub fn KeyEditor(
+    keygen: Rc<KeyTool>,
+    original: String,
+    key: ProductKey,
+    edits: KeyEdits,
+    on_key: Callback<String>,
+) -> impl IntoView {
+    let Some(old) = KeyFields::from_key(&key) else {
+        return view! { <div></div> }.into_view();
+    };
+    let bink_ids = match &key {
+        ProductKey::Bink1998 { bink_ids, .. } | ProductKey::Bink2002 { bink_ids, .. } => bink_ids
+            .values()
+            .copied()
+            .filter(|bink_id| keygen.can_generate(*bink_id))
+            .collect::<BTreeSet<_>>(),
+        _ => BTreeSet::new(),
+    };
+    let Some(first_bink_id) = bink_ids.first().copied() else {
+        return view! {
+            <p>"There's no private key for this key's BINK, so it can't be re-signed."</p>
+        }
+        .into_view();
+    };
+
+    let (bink_id, set_bink_id) = create_signal(first_bink_id);
+
+    let sign_action = create_action(|request: &GenKeyRequest| {
+        let request = request.clone();
+        async move {
+            let key = worker::gen_key(request).await?;
+            let fields = worker::validate_key(key.clone())
+                .await
+                .as_ref()
+                .and_then(KeyFields::from_key)
+                .ok_or_else(|| format!("The new key {key} doesn't validate"))?;
+            Ok::<_, String>((key, fields))
+        }
+    });
+    let signed = sign_action.value();
+
+    let sign = move |_| {
+        sign_action.dispatch(edits.request(bink_id.get()));
+    };
+
+    let update_bink_id = move |ev| {
+        if let Ok(bink_id) = u8::from_str_radix(&event_target_value(&ev), 16) {
+            set_bink_id.set(bink_id);
+        }
+    };
+
+    let old_rows = old.rows();
+    let rows = move || {
+        let new = signed.get().and_then(Result::ok);
+        let new_rows = new.as_ref().map(|(_, fields)| fields.rows());
+        std::iter::once(("Key", original.clone(), new.map(|(key, _)| key)))
+            .chain(old_rows.iter().enumerate().map(|(i, (field, value))| {
+                let new_value = new_rows
+                    .as_ref()
+                    .and_then(|rows| rows.get(i))
+                    .map(|(_, value)| value.clone());
+                (*field, value.clone(), new_value)
+            }))
+            .enumerate()
+            .map(|(i, (field, old_value, new_value))| {
+                let changed = new_value.as_ref().is_some_and(|new| *new != old_value);
+                let class = if i % 2 == 0 {
+                    ""
+                } else {
+                    "bg-slate-100 dark:bg-slate-700"
+                };
+                let new_class = if changed {
+                    "font-mono px-4 py-2 break-all bg-amber-200 dark:bg-amber-700"
+                } else {
+                    "font-mono px-4 py-2 break-all"
+                };
+                view! {
+                    <tr class=class>
+                        <th class="font-semibold px-4 py-2">{field}</th>
+                        <td class="font-mono px-4 py-2 break-all">{old_value}</td>
+                        <td class=new_class>{new_value}</td>
+                    </tr>
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <p class="mb-4">
+            "Change the fields in the Edit column of the details above and sign a new key with the same BINK. Fields that came out different in the new key are highlighted. Leaving the sequence or auth info blank picks a random one."
+        </p>
+        {(bink_ids.len() > 1)
+            .then(|| {
+                view! {
+                    <div class="mb-4">
+                        <Label for_id="editorbink">"BINK"</Label>
+                        <select
+                            id="editorbink"
+                            class="block appearance-none w-full px-3 py-2 rounded-lg bg-slate-100 h-10 dark:bg-slate-900 cursor-pointer"
+                            on:input=update_bink_id
+                        >
+                            {bink_ids
+                                .iter()
+                                .map(|option| {
+                                    let option = *option;
+                                    view! {
+                                        <option
+                                            value=format!("{option:02X}")
+                                            selected=move || bink_id.get() == option
+                                        >
+                                            {format!("{option:02X}")}
+                                        </option>
+                                    }
+                                })
+                                .collect::<Vec<_>>()}
+                        </select>
+                    </div>
+                }
+            })}
+        <div class="overflow-x-auto mb-4">
+            <table class="w-full text-left">
+                <thead>
+                    <tr>
+                        <th class="font-semibold px-4 py-2">"Field"</th>
+                        <th class="font-semibold px-4 py-2">"Old"</th>
+                        <th class="font-semibold px-4 py-2">"New"</th>
+                    </tr>
+                </thead>
+                <tbody>{rows}</tbody>
+            </table>
+        </div>
+        {move || {
+            signed
+                .get()
+                .and_then(Result::err)
+                .map(|err| {
+                    view! {
+                        <div class="mb-4">
+                            <Error>{err}</Error>
+                        </div>
+                    }
+                })
+        }}
+        <div class="flex flex-row gap-4">
+            <Button
+                class="text-slate-100 bg-indigo-500 hover:bg-indigo-600 dark:bg-indigo-600 dark:hover:bg-indigo-700"
+                on_click=sign
+            >
+                "Sign New Key"
+            </Button>
+            <Show when=move || sign_action.pending().get()>
+                <Loading />
+            </Show>
+            <Show when=move || matches!(signed.get(), Some(Ok(_)))>
+                <Button
+                    class="bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600"
+                    on_click=move |_| {
+                        if let Some(Ok((key, _))) = signed.get() {
+                            on_key.call(key);
+                        }
+                    }
+                >
+                    "Validate New Key"
+                </Button>
+            </Show>
+        </div>
+    }
+    .into_view()
+}
@@ -0,0 +1,17 @@
+use leptos::*;
+
+#[component]
+pub fn Loading() -> impl IntoView {
+    view! {
+        <div class="flex items-center justify-center pt-4">
+            <div
+                class="inline-block h-8 w-8 animate-spin rounded-full border-4 border-solid border-current border-e-transparent align-[-0.125em] text-surface motion-reduce:animate-[spin_1.5s_linear_infinite]"
+                role="status"
+            >
+                <span class="!absolute !-m-px !h-px !w-px !overflow-hidden !whitespace-nowrap !border-0 !p-0 ![clip:rect(0,0,0,0)]">
+                    Loading...
+                </span>
+            </div>
+        </div>
+    }
+}
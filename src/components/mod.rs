@@ -1,5 +1,14 @@
+pub mod answer_files;
 pub mod bink1998_details;
 pub mod bink2002_details;
 pub mod button;
 pub mod error;
 pub mod fields;
+pub mod key_editor;
+pub mod loading;
+pub mod number_cells;
+pub mod pidgen2_details;
+pub mod pidgen2_keys;
+pub mod progress;
+pub mod qr_code;
+pub mod warning;
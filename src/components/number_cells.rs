@@ -0,0 +1,25 @@
+use leptos::*;
+
+use crate::settings::{use_settings, NumberFormat};
+
+/// Table cells showing a number from a key in the preferred formats
+#[component]
+pub fn NumberCells(value: String) -> impl IntoView {
+    let settings = use_settings();
+    let hex = value
+        .parse::<u64>()
+        .map(|value| format!("0x{value:X}"))
+        .unwrap_or_default();
+
+    move || {
+        let (first, second) = match settings.get().number_format {
+            NumberFormat::Both => (value.clone(), hex.clone()),
+            NumberFormat::Decimal => (value.clone(), String::new()),
+            NumberFormat::Hex => (hex.clone(), String::new()),
+        };
+        view! {
+            <td class="font-mono px-4 py-2">{first}</td>
+            <td class="font-mono px-4 py-2">{second}</td>
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use leptos::*;
+
+use crate::{
+    components::{fields::Label, number_cells::NumberCells},
+    crypto::{ProductKeyCd, ProductKeyOem},
+};
+
+/// The two-digit years of OEM keys run from 1995 to 2003
+fn full_year(year: &str) -> String {
+    match year.parse::<u32>() {
+        Ok(year) if year >= 95 => format!("19{year:02}"),
+        Ok(year) => format!("20{year:02}"),
+        Err(_) => year.to_string(),
+    }
+}
+
+#[component]
+pub fn Pidgen2CdDetails(key: ProductKeyCd) -> impl IntoView {
+    view! {
+        <div class="mb-4">
+            <Label>"Products"</Label>
+            <p>"Any PIDGEN2 product that takes a CD key, such as Windows 95, NT 4.0 or Office 97"</p>
+        </div>
+        <div class="mb-4">
+            <Label>"Details"</Label>
+            <table class="w-full text-left">
+                <tbody>
+                    <tr>
+                        <th class="font-semibold px-4 py-2">"Format"</th>
+                        <td class="px-4 py-2">"CD key (XXX-XXXXXXX)"</td>
+                        <td></td>
+                    </tr>
+                    <tr class="bg-slate-100 dark:bg-slate-700">
+                        <th class="font-semibold px-4 py-2">"Site"</th>
+                        <NumberCells value=key.site.clone() />
+                    </tr>
+                    <tr>
+                        <th class="font-semibold px-4 py-2">"Serial"</th>
+                        <td class="font-mono px-4 py-2">{key.serial.clone()}</td>
+                        <td></td>
+                    </tr>
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
+#[component]
+pub fn Pidgen2OemDetails(key: ProductKeyOem) -> impl IntoView {
+    view! {
+        <div class="mb-4">
+            <Label>"Products"</Label>
+            <p>"Any PIDGEN2 product that takes an OEM key, such as Windows 95, NT 4.0 or Office 97"</p>
+        </div>
+        <div class="mb-4">
+            <Label>"Details"</Label>
+            <table class="w-full text-left">
+                <tbody>
+                    <tr>
+                        <th class="font-semibold px-4 py-2">"Format"</th>
+                        <td class="px-4 py-2">"OEM key (XXXXX-OEM-XXXXXXX-XXXXX)"</td>
+                        <td></td>
+                    </tr>
+                    <tr class="bg-slate-100 dark:bg-slate-700">
+                        <th class="font-semibold px-4 py-2">"Day of Year"</th>
+                        <NumberCells value=key.day.trim_start_matches('0').to_string() />
+                    </tr>
+                    <tr>
+                        <th class="font-semibold px-4 py-2">"Year"</th>
+                        <td class="font-mono px-4 py-2">{full_year(&key.year)}</td>
+                        <td></td>
+                    </tr>
+                    <tr class="bg-slate-100 dark:bg-slate-700">
+                        <th class="font-semibold px-4 py-2">"Serial"</th>
+                        <td class="font-mono px-4 py-2">{key.serial.clone()}</td>
+                        <td></td>
+                    </tr>
+                    <tr>
+                        <th class="font-semibold px-4 py-2">"Random"</th>
+                        <td class="font-mono px-4 py-2">{key.random.clone()}</td>
+                        <td></td>
+                    </tr>
+                </tbody>
+            </table>
+        </div>
+    }
+}
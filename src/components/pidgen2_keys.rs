@@ -0,0 +1,284 @@
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{
+    components::{
+        button::Button,
+        error::Error,
+        fields::{NumberField, SelectField},
+    },
+    crypto::{ProductKeyCd, ProductKeyOem, OEM_YEARS, PIDGEN2_CD, PIDGEN2_OEM},
+    history::{self, HistoryItem},
+    icons::{CopyIcon, RefreshIcon},
+    storage,
+};
+
+pub const STORAGE_KEY: &str = "mskey-web.pidgen2";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Pidgen2Format {
+    #[default]
+    Cd,
+    Oem,
+}
+
+impl Pidgen2Format {
+    pub const ALL: [Pidgen2Format; 2] = [Pidgen2Format::Cd, Pidgen2Format::Oem];
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.id() == id)
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Pidgen2Format::Cd => "cd",
+            Pidgen2Format::Oem => "oem",
+        }
+    }
+}
+
+impl std::fmt::Display for Pidgen2Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pidgen2Format::Cd => write!(f, "CD (XXX-XXXXXXX)"),
+            Pidgen2Format::Oem => write!(f, "OEM (XXXXX-OEM-XXXXXXX-XXXXX)"),
+        }
+    }
+}
+
+/// The PIDGEN2 inputs, blank ones being random
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+struct FormState {
+    format: Pidgen2Format,
+    site: String,
+    day: String,
+    year: String,
+}
+
+/// Parses an optional number, where blank means pick one at random
+fn optional_number(value: &str) -> Result<Option<u32>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value
+        .parse()
+        .map(Some)
+        .map_err(|_| format!("{value} isn't a number"))
+}
+
+/// Generates Windows 95, NT 4.0 and Office 97-era keys, which only need the digits to add up
+#[component]
+pub fn Pidgen2Keys() -> impl IntoView {
+    let initial = storage::load::<FormState>(STORAGE_KEY).unwrap_or_default();
+    let (format, set_format) = create_signal(initial.format);
+    let (site, set_site) = create_signal(initial.site);
+    let (day, set_day) = create_signal(initial.day);
+    let (year, set_year) = create_signal(initial.year);
+    let (regenerate, set_regenerate) = create_signal(());
+
+    create_effect(move |_| {
+        let form = FormState {
+            format: format.get(),
+            site: site.get(),
+            day: day.get(),
+            year: year.get(),
+        };
+        storage::save(STORAGE_KEY, &form);
+    });
+
+    let key = create_memo(move |_| {
+        regenerate.get();
+        let key = match format.get() {
+            Pidgen2Format::Cd => {
+                let site = optional_number(&site.get())?;
+                ProductKeyCd::new(site, js_sys::Math::random).map(|key| key.to_string())
+            }
+            Pidgen2Format::Oem => {
+                let day = optional_number(&day.get())?;
+                let year = year.get();
+                let year = (!year.is_empty()).then_some(year.as_str());
+                ProductKeyOem::new(day, year, js_sys::Math::random).map(|key| key.to_string())
+            }
+        };
+        key.map_err(|err| err.to_string())
+    });
+
+    // Like on the rest of the Generate tab, a key is only recorded in the history once it's
+    // copied or asked for with Regenerate, and only once
+    let recorded = store_value(None::<String>);
+    let record_key = move || {
+        let Ok(key) = key.get_untracked() else {
+            return;
+        };
+        if recorded.get_value().as_ref() == Some(&key) {
+            return;
+        }
+        recorded.set_value(Some(key.clone()));
+        let product = match format.get_untracked() {
+            Pidgen2Format::Cd => PIDGEN2_CD,
+            Pidgen2Format::Oem => PIDGEN2_OEM,
+        };
+        // PIDGEN2 keys aren't signed, so there's no BINK, channel or sequence to go with them
+        history::record(HistoryItem::Generated {
+            key,
+            product: product.to_string(),
+            bink: String::new(),
+            channel: String::new(),
+            sequence: String::new(),
+            upgrade: false,
+        });
+    };
+
+    let copy_key_action = create_action(|input: &String| {
+        let input = input.clone();
+        async move {
+            JsFuture::from(
+                web_sys::window()
+                    .unwrap()
+                    .navigator()
+                    .clipboard()
+                    .write_text(&input),
+            )
+            .await
+            .err()
+            .map(|_| "Couldn't copy the key to the clipboard".to_string())
+        }
+    });
+
+    let update_format = move |ev| {
+        if let Some(format) = Pidgen2Format::from_id(&event_target_value(&ev)) {
+            set_format.set(format);
+        }
+    };
+
+    view! {
+        <div class="flex flex-col sm:flex-row gap-4 mb-6">
+            <div class="flex-1">
+                <SelectField label="Format" id="pidgen2format" on_input=update_format>
+                    {Pidgen2Format::ALL
+                        .into_iter()
+                        .map(|option| {
+                            view! {
+                                <option
+                                    value=option.id()
+                                    selected=move || format.get() == option
+                                >
+                                    {option.to_string()}
+                                </option>
+                            }
+                        })
+                        .collect::<Vec<_>>()}
+                </SelectField>
+            </div>
+            <Show when=move || format.get() == Pidgen2Format::Cd>
+                <div class="flex-1">
+                    <NumberField
+                        label="Site"
+                        id="pidgen2site"
+                        min=0
+                        max=998
+                        value=site
+                        on_input=move |ev| set_site.set(event_target_value(&ev))
+                        placeholder="Random"
+                    />
+                </div>
+            </Show>
+            <Show when=move || format.get() == Pidgen2Format::Oem>
+                <div class="flex-1">
+                    <NumberField
+                        label="Day of Year"
+                        id="pidgen2day"
+                        min=1
+                        max=366
+                        value=day
+                        on_input=move |ev| set_day.set(event_target_value(&ev))
+                        placeholder="Random"
+                    />
+                </div>
+                <div class="flex-1">
+                    <SelectField
+                        label="Year"
+                        id="pidgen2year"
+                        on_input=move |ev| set_year.set(event_target_value(&ev))
+                    >
+                        <option value="" selected=move || year.get().is_empty()>
+                            "Random"
+                        </option>
+                        {OEM_YEARS
+                            .into_iter()
+                            .map(|option| {
+                                view! {
+                                    <option value=option selected=move || year.get() == option>
+                                        {option}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </SelectField>
+                </div>
+            </Show>
+        </div>
+        {move || match key.get() {
+            Ok(key) => {
+                view! {
+                    <div>
+                        <div class="mb-8 mx-auto font-mono text-center text-2xl text-slate-800 rounded-lg p-6 bg-slate-200 dark:bg-slate-700 dark:text-slate-200">
+                            {key}
+                        </div>
+                    </div>
+                }
+            }
+            Err(err) => {
+                view! {
+                    <div class="mb-8">
+                        <Error>{err}</Error>
+                    </div>
+                }
+            }
+        }}
+        <div class="flex flex-row gap-4">
+            <Button
+                class="text-slate-100 bg-emerald-500 hover:bg-emerald-600 dark:bg-emerald-600 dark:hover:bg-emerald-700"
+                on_click=move |_| {
+                    if let Ok(key) = key.get() {
+                        record_key();
+                        copy_key_action.dispatch(key);
+                    }
+                }
+            >
+                <span class="mr-2">
+                    <CopyIcon />
+                </span>
+                <span>"Copy"</span>
+            </Button>
+            <Button
+                class="text-slate-100 bg-indigo-500 hover:bg-indigo-600 dark:bg-indigo-600 dark:hover:bg-indigo-700"
+                on_click=move |_| {
+                    set_regenerate.set(());
+                    record_key();
+                }
+            >
+                <span class="mr-2">
+                    <RefreshIcon />
+                </span>
+                <span>"Regenerate"</span>
+            </Button>
+        </div>
+        {move || {
+            copy_key_action
+                .value()
+                .get()
+                .flatten()
+                .map(|err| {
+                    view! {
+                        <div class="mt-4">
+                            <Error>{err}</Error>
+                        </div>
+                    }
+                })
+        }}
+    }
+}
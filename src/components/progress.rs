@@ -0,0 +1,68 @@
+use leptos::*;
+
+use crate::{
+    components::button::Button,
+    jobs::{JobHandle, JobStatus},
+};
+
+/// A progress bar for a job, with a cancel button while it's running
+#[component]
+pub fn JobProgress(job: JobHandle) -> impl IntoView {
+    let percent = move || {
+        let total = job.total.get();
+        if total == 0 {
+            0
+        } else {
+            job.done.get() * 100 / total
+        }
+    };
+    let errors = move || {
+        job.results
+            .with(|results| results.iter().filter(|update| update.result.is_err()).count())
+    };
+    let status = move || {
+        let progress = format!("{} / {}", job.done.get(), job.total.get());
+        let status = match job.status.get() {
+            JobStatus::Idle => return String::new(),
+            JobStatus::Running => progress,
+            JobStatus::Finished => format!("{progress} done"),
+            JobStatus::Cancelled => format!("{progress}, cancelled"),
+            JobStatus::Failed(err) => format!("Failed: {err}"),
+        };
+        match errors() {
+            0 => status,
+            1 => format!("{status} (1 error)"),
+            errors => format!("{status} ({errors} errors)"),
+        }
+    };
+
+    view! {
+        <Show when=move || job.status.get() != JobStatus::Idle>
+            <div class="flex flex-row items-center gap-4">
+                <div class="flex-1">
+                    <div
+                        class="h-3 w-full rounded-full overflow-hidden bg-slate-200 dark:bg-slate-700"
+                        role="progressbar"
+                        aria-valuemin="0"
+                        aria-valuemax="100"
+                        aria-valuenow=move || percent().to_string()
+                    >
+                        <div
+                            class="h-full rounded-full bg-indigo-500 dark:bg-indigo-600 transition-all"
+                            style=move || format!("width: {}%", percent())
+                        ></div>
+                    </div>
+                    <div class="mt-1 text-sm text-slate-600 dark:text-slate-400">{status}</div>
+                </div>
+                <Show when=move || job.is_running()>
+                    <Button
+                        class="text-slate-100 bg-rose-600 hover:bg-rose-700 dark:bg-rose-700 dark:hover:bg-rose-800"
+                        on_click=move |_| job.cancel()
+                    >
+                        "Cancel"
+                    </Button>
+                </Show>
+            </div>
+        </Show>
+    }
+}
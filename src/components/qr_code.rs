@@ -0,0 +1,104 @@
+use leptos::*;
+use qrcode::{render::svg, EcLevel};
+
+use crate::{
+    components::{button::Button, fields::SelectField},
+    settings::{use_settings, QrErrorCorrection, QrSize},
+};
+
+/// Draws text as a QR code, returning `None` if there's nothing to draw or it doesn't fit
+pub fn render_svg(text: &str, size: QrSize, error_correction: QrErrorCorrection) -> Option<String> {
+    if text.is_empty() {
+        return None;
+    }
+    let level = match error_correction {
+        QrErrorCorrection::Low => EcLevel::L,
+        QrErrorCorrection::Medium => EcLevel::M,
+        QrErrorCorrection::Quartile => EcLevel::Q,
+        QrErrorCorrection::High => EcLevel::H,
+    };
+    let code = qrcode::QrCode::with_error_correction_level(text, level).ok()?;
+    Some(
+        code.render::<svg::Color>()
+            .min_dimensions(size.pixels(), size.pixels())
+            .dark_color(svg::Color("#000000"))
+            .light_color(svg::Color("#ffffff"))
+            .build(),
+    )
+}
+
+/// A button that reveals the value as a QR code, for scanning off the screen with a phone
+#[component]
+pub fn QrCode(#[prop(into)] value: Signal<String>) -> impl IntoView {
+    let settings = use_settings();
+    let (shown, set_shown) = create_signal(false);
+
+    let svg = move || {
+        let settings = settings.get();
+        render_svg(&value.get(), settings.qr_size, settings.qr_error_correction).unwrap_or_default()
+    };
+
+    let update_size = move |ev| {
+        if let Some(size) = QrSize::from_id(&event_target_value(&ev)) {
+            settings.update(|settings| settings.qr_size = size);
+        }
+    };
+
+    let update_error_correction = move |ev| {
+        if let Some(level) = QrErrorCorrection::from_id(&event_target_value(&ev)) {
+            settings.update(|settings| settings.qr_error_correction = level);
+        }
+    };
+
+    view! {
+        <Button
+            class="bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600"
+            on_click=move |_| set_shown.update(|shown| *shown = !*shown)
+        >
+            {move || if shown.get() { "Hide QR Code" } else { "Show QR Code" }}
+        </Button>
+        <Show when=move || shown.get()>
+            <div class="mt-4 flex flex-col sm:flex-row gap-4 items-start">
+                <div class="rounded-lg overflow-hidden bg-white" inner_html=svg></div>
+                <div class="flex-1 w-full">
+                    <div class="mb-4">
+                        <SelectField label="QR Code Size" id="qrsize" on_input=update_size>
+                            {move || {
+                                let selected = settings.get().qr_size;
+                                QrSize::ALL
+                                    .into_iter()
+                                    .map(|size| {
+                                        view! {
+                                            <option value=size.id() selected=size == selected>
+                                                {size.to_string()}
+                                            </option>
+                                        }
+                                    })
+                                    .collect::<Vec<_>>()
+                            }}
+                        </SelectField>
+                    </div>
+                    <SelectField
+                        label="Error Correction"
+                        id="qrerrorcorrection"
+                        on_input=update_error_correction
+                    >
+                        {move || {
+                            let selected = settings.get().qr_error_correction;
+                            QrErrorCorrection::ALL
+                                .into_iter()
+                                .map(|level| {
+                                    view! {
+                                        <option value=level.id() selected=level == selected>
+                                            {level.to_string()}
+                                        </option>
+                                    }
+                                })
+                                .collect::<Vec<_>>()
+                        }}
+                    </SelectField>
+                </div>
+            </div>
+        </Show>
+    }
+}
@@ -0,0 +1,15 @@
+use leptos::*;
+
+use crate::icons::WarningIcon;
+
+#[component]
+pub fn Warning(children: Children) -> impl IntoView {
+    view! {
+        <div class="shadow-sm rounded-lg px-4 py-4 text-slate-900 dark:text-slate-50 bg-amber-300 dark:bg-amber-700">
+            <span class="mr-2 -mt-4">
+                <WarningIcon />
+            </span>
+            {children()}
+        </div>
+    }
+}
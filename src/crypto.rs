@@ -1,7 +1,13 @@
 use std::collections::HashMap;
 
 use anyhow::{anyhow, bail, Result};
-use bink_types::bink::Keys;
+use bink_types::{
+    bink::{self, Bink, Keys},
+    channels::{ChannelKind, ChannelRange, Channels},
+    curve::{self, Curve},
+};
+use num_bigint::BigInt;
+use num_traits::{Num, One};
 use serde::{Deserialize, Serialize};
 use umskt::{
     confid,
@@ -9,6 +15,20 @@ use umskt::{
     pidgen3::{bink1998, bink2002},
 };
 
+pub use pidgen2::{ProductKeyCd, ProductKeyOem, OEM_YEARS};
+
+/// BINK2002 auth info is 10 bits
+pub const MAX_AUTH_INFO: u32 = 0x3FF;
+
+/// The characters of a product key, indexed by their base-24 digit
+pub const KEY_CHARS: &[u8; 24] = b"BCDFGHJKMPQRTVWXY2346789";
+
+/// What PIDGEN2 keys are listed as in place of a product, since they aren't tied to the dataset
+pub const PIDGEN2_CD: &str = "PIDGEN2 CD key";
+pub const PIDGEN2_OEM: &str = "PIDGEN2 OEM key";
+
+mod pidgen2;
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ProductKey1998 {
     pub upgrade: bool,
@@ -51,17 +71,25 @@ impl From<bink2002::ProductKey> for ProductKey2002 {
     }
 }
 
-/// Differentiate between the two types of product keys and an invalid key
+/// Differentiate between the types of product keys and an invalid key
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum ProductKey {
     Empty,
     Bink1998 {
         key: ProductKey1998,
         bink_ids: HashMap<String, u8>,
+        channel_kinds: HashMap<String, ChannelKind>,
     },
     Bink2002 {
         key: ProductKey2002,
         bink_ids: HashMap<String, u8>,
+        channel_kinds: HashMap<String, ChannelKind>,
+    },
+    Pidgen2Cd {
+        key: ProductKeyCd,
+    },
+    Pidgen2Oem {
+        key: ProductKeyOem,
     },
     Invalid,
 }
@@ -70,6 +98,45 @@ pub enum ProductKey {
 pub struct Product {
     pub name: String,
     pub bink_ids: Vec<u8>,
+    pub family: String,
+    pub year: Option<u16>,
+    pub edition: Option<String>,
+    pub bink_labels: HashMap<u8, String>,
+    pub codes: Vec<String>,
+}
+
+impl Product {
+    /// Returns the label for a BINK ID, e.g. "2E (Retail)"
+    pub fn bink_label(&self, bink_id: u8) -> String {
+        match self.bink_labels.get(&bink_id) {
+            Some(label) => format!("{:02X} ({})", bink_id, label),
+            None => format!("{:02X}", bink_id),
+        }
+    }
+
+    /// Checks whether any of the product's names or metadata contain the search text
+    pub fn matches(&self, search: &str) -> bool {
+        let search = search.trim().to_lowercase();
+        if search.is_empty() {
+            return true;
+        }
+        std::iter::once(&self.name)
+            .chain(std::iter::once(&self.family))
+            .chain(self.edition.iter())
+            .chain(self.codes.iter())
+            .any(|field| field.to_lowercase().contains(&search))
+            || self
+                .year
+                .is_some_and(|year| year.to_string().contains(&search))
+    }
+}
+
+/// Falls back to the first two words of the product name, e.g. "Windows XP"
+fn infer_family(name: &str) -> String {
+    name.split_whitespace()
+        .take(2)
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 impl PartialEq for Product {
@@ -98,16 +165,173 @@ pub struct KeyTool {
     products: Vec<Product>,
 }
 
+/// A user-supplied BINK, kept as entered so it can be edited and saved
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomBink {
+    pub name: String,
+    pub bink_id: String,
+    pub p: String,
+    pub a: String,
+    pub b: String,
+    pub gx: String,
+    pub gy: String,
+    pub kx: String,
+    pub ky: String,
+    pub n: String,
+    pub private: String,
+}
+
+impl CustomBink {
+    /// Parses and checks the curve parameters, returning a `KeyTool` with the BINK as its only product
+    pub fn key_tool(&self) -> Result<KeyTool> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            bail!("Name is required");
+        }
+        let bink_id = u8::from_str_radix(self.bink_id.trim(), 16)
+            .map_err(|_| anyhow!("BINK ID must be a hex byte"))?;
+
+        let p = parse_bigint("p", &self.p)?;
+        let a = parse_bigint("a", &self.a)?;
+        let b = parse_bigint("b", &self.b)?;
+        let g = bink::Point {
+            x: parse_bigint("G.x", &self.gx)?,
+            y: parse_bigint("G.y", &self.gy)?,
+        };
+        let public = bink::Point {
+            x: parse_bigint("K.x", &self.kx)?,
+            y: parse_bigint("K.y", &self.ky)?,
+        };
+        let n = parse_bigint("n", &self.n)?;
+        let private = if self.private.trim().is_empty() {
+            None
+        } else {
+            Some(parse_bigint("Private key", &self.private)?)
+        };
+
+        if p <= BigInt::from(3) || !curve::is_probable_prime(&p) {
+            bail!("p must be a prime greater than 3");
+        }
+        let arithmetic = Curve {
+            p: p.clone(),
+            a: a.clone(),
+            b: b.clone(),
+        };
+        if !arithmetic.contains(&curve::point(&g)) {
+            bail!("G is not on the curve");
+        }
+        if !arithmetic.contains(&curve::point(&public)) {
+            bail!("Public key is not on the curve");
+        }
+        if n <= BigInt::one() {
+            bail!("n must be greater than 1");
+        }
+        if arithmetic.mul(&n, &curve::point(&g)).is_some() {
+            bail!("n is not the order of G");
+        }
+        if let Some(private) = &private {
+            if *private < BigInt::one() || *private >= n {
+                bail!("Private key must be from 1 to n - 1");
+            }
+            // umskt signs for K = -kG, which is how keys.json stores them
+            let product = arithmetic.mul(private, &curve::point(&g));
+            if arithmetic.neg(&product) != curve::point(&public) {
+                bail!("Private key doesn't match the public key");
+            }
+        }
+
+        let keys = Keys {
+            products: HashMap::from([(
+                name.to_string(),
+                bink::Product {
+                    bink: vec![bink_id],
+                    family: Some("Custom".to_string()),
+                    year: None,
+                    edition: None,
+                    bink_labels: HashMap::new(),
+                    codes: Vec::new(),
+                },
+            )]),
+            bink: HashMap::from([(
+                bink_id,
+                Bink {
+                    p,
+                    a,
+                    b,
+                    g,
+                    public,
+                    n,
+                    private,
+                },
+            )]),
+            channels: Channels::default(),
+        };
+        Ok(KeyTool::from_keys(keys))
+    }
+}
+
+/// Parses a big integer written in decimal, or in hex with a `0x` prefix
+fn parse_bigint(name: &str, value: &str) -> Result<BigInt> {
+    let value = value.trim();
+    let parsed = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => BigInt::from_str_radix(hex, 16),
+        None => BigInt::from_str_radix(value, 10),
+    };
+    parsed.map_err(|_| anyhow!("{name} must be a decimal or 0x-prefixed hex number"))
+}
+
+/// The channel ID and sequence of a BINK1998 key, read straight out of it without checking its
+/// signature
+pub fn bink1998_serial(key: &str) -> Option<(u32, u32)> {
+    let serial = (decode_key(key)? & ((1 << 31) - 1)) as u32 >> 1;
+    Some((serial / 1_000_000, serial % 1_000_000))
+}
+
+/// Reads a key back out of base 24, ignoring dashes, or `None` if it isn't 25 key characters
+fn decode_key(key: &str) -> Option<u128> {
+    let digits: Vec<u8> = key.bytes().filter(|&c| c != b'-').collect();
+    if digits.len() != 25 {
+        return None;
+    }
+    digits.iter().try_fold(0_u128, |packed, digit| {
+        let value = KEY_CHARS.iter().position(|c| c == digit)?;
+        Some(packed * 24 + value as u128)
+    })
+}
+
+/// A random number below `bound`, for blank sequences and auth info
+fn random_below(bound: u32, random: &mut impl FnMut(&mut [u8]) -> Result<()>) -> Result<u32> {
+    let mut bytes = [0; 8];
+    random(&mut bytes)?;
+    Ok((u64::from_le_bytes(bytes) % bound as u64) as u32)
+}
+
 impl KeyTool {
     /// Initialize key crypto with the serialized key data in `bink.bin`
     pub fn new() -> Self {
         let keys: Keys = bincode::deserialize(std::include_bytes!("../bink.bin")).unwrap();
+        Self::from_keys(keys)
+    }
+
+    /// Initialize key crypto with key data from somewhere other than `bink.bin`
+    pub fn from_keys(keys: Keys) -> Self {
         let mut products: Vec<Product> = keys
             .products
             .iter()
             .map(|(name, product)| Product {
                 name: name.clone(),
                 bink_ids: product.bink.clone(),
+                family: product
+                    .family
+                    .clone()
+                    .unwrap_or_else(|| infer_family(name)),
+                year: product.year,
+                edition: product.edition.clone(),
+                bink_labels: product.bink_labels.clone(),
+                codes: product.codes.clone(),
             })
             .collect();
         products.sort();
@@ -118,12 +342,122 @@ impl KeyTool {
         &self.products
     }
 
+    /// Groups the sorted products by family, with families in alphabetical order
+    pub fn product_families(&self) -> Vec<(&str, Vec<&Product>)> {
+        let mut families: Vec<(&str, Vec<&Product>)> = Vec::new();
+        for product in &self.products {
+            match families
+                .iter_mut()
+                .find(|(family, _)| *family == product.family)
+            {
+                Some((_, products)) => products.push(product),
+                None => families.push((&product.family, vec![product])),
+            }
+        }
+        families.sort_by(|a, b| a.0.cmp(b.0));
+        families
+    }
+
+    /// Returns every BINK ID in the dataset in ascending order
+    pub fn bink_ids(&self) -> Vec<u8> {
+        let mut bink_ids: Vec<u8> = self.keys.bink.keys().copied().collect();
+        bink_ids.sort();
+        bink_ids
+    }
+
+    pub fn bink(&self, bink_id: u8) -> Option<&Bink> {
+        self.keys.bink.get(&bink_id)
+    }
+
+    /// Checks whether the dataset has the private key needed to generate keys for a BINK
+    pub fn can_generate(&self, bink_id: u8) -> bool {
+        self.bink(bink_id).is_some_and(|bink| bink.private.is_some())
+    }
+
+    /// Checks whether any BINK in the dataset can be used to generate keys
+    pub fn can_generate_any(&self) -> bool {
+        self.keys.bink.values().any(|bink| bink.private.is_some())
+    }
+
+    /// Returns the names of all products that use a BINK ID, sorted alphabetically
+    pub fn bink_products(&self, bink_id: u8) -> Vec<&str> {
+        self.products
+            .iter()
+            .filter(|product| product.bink_ids.contains(&bink_id))
+            .map(|product| product.name.as_str())
+            .collect()
+    }
+
+    /// Serializes a BINK's public curve parameters in the same form as `keys.json`
+    pub fn bink_json(&self, bink_id: u8) -> Result<String> {
+        let bink = self.bink(bink_id).ok_or(anyhow!("Bink ID not found"))?;
+        let json = serde_json::json!({
+            format!("{:02X}", bink_id): {
+                "p": bink.p.to_string(),
+                "a": bink.a.to_string(),
+                "b": bink.b.to_string(),
+                "g": { "x": bink.g.x.to_string(), "y": bink.g.y.to_string() },
+                "pub": { "x": bink.public.x.to_string(), "y": bink.public.y.to_string() },
+                "n": bink.n.to_string(),
+            }
+        });
+        Ok(serde_json::to_string_pretty(&json)?)
+    }
+
+    /// Returns the channel ranges from the channel catalogue that apply to a product
+    pub fn channel_presets(&self, product: &str) -> &[ChannelRange] {
+        self.keys.channels.ranges(product)
+    }
+
+    /// Looks up what a channel ID means for a product, if the catalogue knows it
+    pub fn channel_kind(&self, product: &str, channel_id: u32) -> Option<ChannelKind> {
+        self.keys.channels.kind(product, channel_id)
+    }
+
+    fn channel_kinds(
+        &self,
+        bink_ids: &HashMap<String, u8>,
+        channel_id: u32,
+    ) -> HashMap<String, ChannelKind> {
+        bink_ids
+            .keys()
+            .filter_map(|name| {
+                self.channel_kind(name, channel_id)
+                    .map(|kind| (name.clone(), kind))
+            })
+            .collect()
+    }
+
+    /// Signs a key with the BINK's private key. The sequence is only used by BINK1998 and the
+    /// auth info only by BINK2002, and either is random when left blank.
     pub fn gen_key(
         &self,
         bink_id: u8,
         channel_id: &str,
         sequence: &str,
+        auth_info: &str,
         upgrade: bool,
+    ) -> Result<String> {
+        self.gen_key_with(
+            bink_id,
+            channel_id,
+            sequence,
+            auth_info,
+            upgrade,
+            |bytes: &mut [u8]| getrandom::getrandom(bytes).map_err(|err| anyhow!("{err}")),
+        )
+    }
+
+    /// Like `gen_key`, but with a blank sequence or auth info drawn from `random`, so a fixed
+    /// source gives fixed fields. umskt still picks the signature's nonce itself.
+    pub fn gen_key_with(
+        &self,
+        bink_id: u8,
+        channel_id: &str,
+        sequence: &str,
+        auth_info: &str,
+        upgrade: bool,
+        mut random: impl FnMut(&mut [u8]) -> Result<()>,
     ) -> Result<String> {
         let channel_id = channel_id.parse::<u32>()?;
 
@@ -141,6 +475,16 @@ impl KeyTool {
             Some(sequence)
         };
 
+        let auth_info = if auth_info.is_empty() {
+            None
+        } else {
+            let auth_info = auth_info.parse::<u32>()?;
+            if auth_info > MAX_AUTH_INFO {
+                bail!("Auth info must be {MAX_AUTH_INFO} or less");
+            }
+            Some(auth_info)
+        };
+
         let bink = self
             .keys
             .bink
@@ -155,26 +499,51 @@ impl KeyTool {
             bink.public.x.clone(),
             bink.public.y.clone(),
         );
-        let private_key = PrivateKey::new(bink.n.clone(), bink.private.clone());
+
+        let private = bink
+            .private
+            .clone()
+            .ok_or(anyhow!("No private key for this BINK"))?;
+        let private_key = PrivateKey::new(bink.n.clone(), private);
 
         if bink_id < 0x40 {
+            let sequence = match sequence {
+                Some(sequence) => sequence,
+                None => random_below(1_000_000, &mut random)?,
+            };
             Ok(bink1998::ProductKey::new(
                 &curve,
                 &private_key,
                 channel_id,
-                sequence,
+                Some(sequence),
                 Some(upgrade),
             )?
             .to_string())
         } else {
-            Ok(
-                bink2002::ProductKey::new(&curve, &private_key, channel_id, None, Some(upgrade))?
-                    .to_string(),
-            )
+            let auth_info = match auth_info {
+                Some(auth_info) => auth_info,
+                None => random_below(MAX_AUTH_INFO + 1, &mut random)?,
+            };
+            Ok(bink2002::ProductKey::new(
+                &curve,
+                &private_key,
+                channel_id,
+                Some(auth_info),
+                Some(upgrade),
+            )?
+            .to_string())
         }
     }
 
     pub fn validate_key(&self, key: &str) -> Result<ProductKey> {
+        // PIDGEN2 keys have a shape of their own and aren't tied to a BINK
+        if let Some(key) = ProductKeyCd::from_key(key) {
+            return Ok(ProductKey::Pidgen2Cd { key });
+        }
+        if let Some(key) = ProductKeyOem::from_key(key) {
+            return Ok(ProductKey::Pidgen2Oem { key });
+        }
+
         let mut valid_bink_ids = HashMap::new();
         let mut product_key_bink1998: Option<bink1998::ProductKey> = None;
         let mut product_key_bink2002: Option<bink2002::ProductKey> = None;
@@ -208,16 +577,20 @@ impl KeyTool {
         }
 
         if let Some(product_key) = product_key_bink1998 {
+            let channel_kinds = self.channel_kinds(&valid_bink_ids, product_key.channel_id());
             return Ok(ProductKey::Bink1998 {
                 key: product_key.into(),
                 bink_ids: valid_bink_ids,
+                channel_kinds,
             });
         }
 
         if let Some(product_key) = product_key_bink2002 {
+            let channel_kinds = self.channel_kinds(&valid_bink_ids, product_key.channel_id());
             return Ok(ProductKey::Bink2002 {
                 key: product_key.into(),
                 bink_ids: valid_bink_ids,
+                channel_kinds,
             });
         }
 
@@ -229,3 +602,6 @@ impl KeyTool {
         Ok(confid::generate(&installation_id)?)
     }
 }
+
+#[cfg(test)]
+mod tests;
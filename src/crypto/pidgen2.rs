@@ -0,0 +1,163 @@
+//! PIDGEN2 keys, as used by Windows 95, NT 4.0 and Office 97. There's no signature, just a
+//! mod-7 check on the digits, so any key that passes the check is accepted by every product
+//! using the same format.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// Sites that CD keys can't start with
+const BANNED_SITES: [u32; 7] = [333, 444, 555, 666, 777, 888, 999];
+
+/// Two-digit years that OEM keys are accepted with
+pub const OEM_YEARS: [&str; 9] = ["95", "96", "97", "98", "99", "00", "01", "02", "03"];
+
+/// Whether the digits add up to a multiple of 7, with a last digit from 1 to 7
+fn mod7_valid(digits: &str) -> bool {
+    let sum: u32 = digits.bytes().map(|digit| (digit - b'0') as u32).sum();
+    sum.is_multiple_of(7) && matches!(digits.bytes().last(), Some(b'1'..=b'7'))
+}
+
+/// Appends the digit that makes the digits pass the mod-7 check
+fn with_check_digit(digits: String) -> String {
+    let sum: u32 = digits.bytes().map(|digit| (digit - b'0') as u32).sum();
+    let check = match (7 - sum % 7) % 7 {
+        0 => 7,
+        check => check,
+    };
+    format!("{digits}{check}")
+}
+
+/// `count` digits from a source of random numbers in [0, 1)
+fn random_digits(count: usize, random: &mut impl FnMut() -> f64) -> String {
+    (0..count)
+        .map(|_| char::from(b'0' + (random() * 10.0) as u8))
+        .collect()
+}
+
+fn is_digits(text: &str, length: usize) -> bool {
+    text.len() == length && text.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+/// A retail CD key, `XXX-XXXXXXX`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProductKeyCd {
+    pub site: String,
+    pub serial: String,
+}
+
+impl ProductKeyCd {
+    pub fn from_key(key: &str) -> Option<Self> {
+        let (site, serial) = key.trim().split_once('-')?;
+        if !is_digits(site, 3) || !is_digits(serial, 7) {
+            return None;
+        }
+        let banned = BANNED_SITES.contains(&site.parse().ok()?);
+        (!banned && mod7_valid(serial)).then(|| ProductKeyCd {
+            site: site.to_string(),
+            serial: serial.to_string(),
+        })
+    }
+
+    /// Makes a key for the site, or a random one if there isn't one, given a source of random
+    /// numbers in [0, 1)
+    pub fn new(site: Option<u32>, mut random: impl FnMut() -> f64) -> Result<Self> {
+        let site = match site {
+            Some(site) if site > 999 => bail!("Site must be 3 digits or fewer"),
+            Some(site) if BANNED_SITES.contains(&site) => {
+                bail!(
+                    "Site can't be {}",
+                    BANNED_SITES.map(|site| site.to_string()).join(", ")
+                )
+            }
+            Some(site) => site,
+            None => loop {
+                let site = (random() * 1000.0) as u32;
+                if !BANNED_SITES.contains(&site) {
+                    break site;
+                }
+            },
+        };
+        Ok(ProductKeyCd {
+            site: format!("{site:03}"),
+            serial: with_check_digit(random_digits(6, &mut random)),
+        })
+    }
+}
+
+impl std::fmt::Display for ProductKeyCd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.site, self.serial)
+    }
+}
+
+/// An OEM key, `DDDYY-OEM-0XXXXXX-XXXXX`, dated by the day of the year and the year
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProductKeyOem {
+    pub day: String,
+    pub year: String,
+    pub serial: String,
+    pub random: String,
+}
+
+impl ProductKeyOem {
+    pub fn from_key(key: &str) -> Option<Self> {
+        let groups = key.trim().split('-').collect::<Vec<_>>();
+        let [date, oem, serial, random] = groups.as_slice() else {
+            return None;
+        };
+        let shaped = is_digits(date, 5)
+            && oem.eq_ignore_ascii_case("OEM")
+            && is_digits(serial, 7)
+            && is_digits(random, 5);
+        if !shaped {
+            return None;
+        }
+        let (day, year) = date.split_at(3);
+        let day_valid = (1..=366).contains(&day.parse::<u32>().ok()?);
+        let valid =
+            day_valid && OEM_YEARS.contains(&year) && serial.starts_with('0') && mod7_valid(serial);
+        valid.then(|| ProductKeyOem {
+            day: day.to_string(),
+            year: year.to_string(),
+            serial: serial.to_string(),
+            random: random.to_string(),
+        })
+    }
+
+    /// Makes a key dated on the day and year, which are random if not given, from a source of
+    /// random numbers in [0, 1)
+    pub fn new(
+        day: Option<u32>,
+        year: Option<&str>,
+        mut random: impl FnMut() -> f64,
+    ) -> Result<Self> {
+        let day = match day {
+            Some(day) if !(1..=366).contains(&day) => bail!("Day must be from 1 to 366"),
+            Some(day) => day,
+            None => (random() * 366.0) as u32 + 1,
+        };
+        let year = match year {
+            Some(year) if !OEM_YEARS.contains(&year) => {
+                bail!("Year must be one of {}", OEM_YEARS.join(", "))
+            }
+            Some(year) => year.to_string(),
+            None => OEM_YEARS[(random() * OEM_YEARS.len() as f64) as usize].to_string(),
+        };
+        Ok(ProductKeyOem {
+            day: format!("{day:03}"),
+            year,
+            serial: with_check_digit(format!("0{}", random_digits(5, &mut random))),
+            random: random_digits(5, &mut random),
+        })
+    }
+}
+
+impl std::fmt::Display for ProductKeyOem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}-OEM-{}-{}",
+            self.day, self.year, self.serial, self.random
+        )
+    }
+}
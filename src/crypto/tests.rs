@@ -0,0 +1,451 @@
+//! Tests against the toy curves in `testdata/keys.json`.
+//!
+//! Blank sequences and auth info are drawn from a seeded byte source, so the fields they come out
+//! as are fixed, and validation is checked against fixed keys with known hashes and signatures.
+
+use proptest::prelude::*;
+
+use super::*;
+use crate::test_util::{cycle, key_tool, test_keys};
+
+const BINK_1998: u8 = 0x01;
+const BINK_2002: u8 = 0x41;
+
+fn validate_1998(key_tool: &KeyTool, key: &str) -> (ProductKey1998, HashMap<String, u8>) {
+    match key_tool.validate_key(key).unwrap() {
+        ProductKey::Bink1998 { key, bink_ids, .. } => (key, bink_ids),
+        other => panic!("expected a BINK1998 key, got {other:?}"),
+    }
+}
+
+fn validate_2002(key_tool: &KeyTool, key: &str) -> (ProductKey2002, HashMap<String, u8>) {
+    match key_tool.validate_key(key).unwrap() {
+        ProductKey::Bink2002 { key, bink_ids, .. } => (key, bink_ids),
+        other => panic!("expected a BINK2002 key, got {other:?}"),
+    }
+}
+
+#[test]
+fn products_are_sorted_and_grouped() {
+    let key_tool = key_tool();
+    let names: Vec<&str> = key_tool
+        .sorted_products()
+        .iter()
+        .map(|p| p.name.as_str())
+        .collect();
+    assert_eq!(names, ["Test 1998", "Test 2002"]);
+
+    let families = key_tool.product_families();
+    assert_eq!(families.len(), 1);
+    assert_eq!(families[0].0, "Test");
+    assert_eq!(families[0].1.len(), 2);
+}
+
+#[test]
+fn gen_key_formats_keys() {
+    let key_tool = key_tool();
+    for bink_id in [BINK_1998, BINK_2002] {
+        let key = key_tool.gen_key(bink_id, "640", "", "", false).unwrap();
+        let groups: Vec<&str> = key.split('-').collect();
+        assert_eq!(groups.len(), 5, "{key}");
+        assert!(groups.iter().all(|group| group.len() == 5), "{key}");
+        assert!(groups.concat().chars().all(|c| KEY_CHARS.contains(&(c as u8))), "{key}");
+    }
+}
+
+#[test]
+fn gen_key_rejects_bad_input() {
+    let key_tool = key_tool();
+    assert!(key_tool.gen_key(BINK_1998, "1000", "", "", false).is_err());
+    assert!(key_tool.gen_key(BINK_1998, "abc", "", "", false).is_err());
+    assert!(key_tool.gen_key(BINK_1998, "", "", "", false).is_err());
+    assert!(key_tool.gen_key(BINK_1998, "640", "1000000", "", false).is_err());
+    assert!(key_tool.gen_key(0x2E, "640", "", "", false).is_err());
+    assert!(key_tool.gen_key(BINK_2002, "640", "", "1024", false).is_err());
+    assert!(key_tool.gen_key(BINK_2002, "640", "", "abc", false).is_err());
+}
+
+#[test]
+fn gen_key_requires_private_key() {
+    let mut keys = test_keys();
+    keys.strip_private();
+    let key_tool = KeyTool::from_keys(keys);
+    assert!(!key_tool.can_generate_any());
+    assert!(!key_tool.can_generate(BINK_1998));
+    assert!(key_tool.gen_key(BINK_1998, "640", "", "", false).is_err());
+    assert!(key_tool.gen_key(BINK_2002, "640", "", "", false).is_err());
+}
+
+/// A deterministic stand-in for getrandom (SplitMix64), so blank fields come out the same each time
+fn seeded(mut state: u64) -> impl FnMut(&mut [u8]) -> Result<()> {
+    move |bytes| {
+        for byte in bytes.iter_mut() {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            *byte = (z ^ (z >> 31)) as u8;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn gen_key_draws_blank_sequences_from_the_source() {
+    let key_tool = key_tool();
+    for (seed, expected) in [(4, "130314"), (5, "943514"), (6, "993216")] {
+        let key = key_tool
+            .gen_key_with(BINK_1998, "640", "", "", false, seeded(seed))
+            .unwrap();
+        let (product_key, _) = validate_1998(&key_tool, &key);
+        assert_eq!(product_key.sequence, expected, "seed {seed}");
+        assert_eq!(product_key.channel_id, "640");
+    }
+
+    // A sequence that's filled in doesn't come from the source
+    let key = key_tool
+        .gen_key_with(BINK_1998, "640", "42", "", false, seeded(4))
+        .unwrap();
+    assert_eq!(validate_1998(&key_tool, &key).0.sequence, "42");
+}
+
+#[test]
+fn gen_key_draws_blank_auth_info_from_the_source() {
+    let key_tool = key_tool();
+    for (seed, expected) in [(4, "202"), (5, "90"), (6, "256")] {
+        let key = key_tool
+            .gen_key_with(BINK_2002, "640", "", "", true, seeded(seed))
+            .unwrap();
+        let (product_key, _) = validate_2002(&key_tool, &key);
+        assert_eq!(product_key.auth_info, expected, "seed {seed}");
+        assert!(product_key.upgrade);
+    }
+}
+
+#[test]
+fn gen_key_reports_source_failures() {
+    let key_tool = key_tool();
+    let failing = |_: &mut [u8]| -> Result<()> { Err(anyhow!("no entropy")) };
+    let err = key_tool
+        .gen_key_with(BINK_1998, "640", "", "", false, failing)
+        .unwrap_err();
+    assert_eq!(err.to_string(), "no entropy");
+    // Nothing is drawn when the field is filled in
+    assert!(key_tool
+        .gen_key_with(BINK_1998, "640", "1", "", false, failing)
+        .is_ok());
+}
+
+#[test]
+fn validate_key_bink1998_known_fields() {
+    let key_tool = key_tool();
+    let (product_key, bink_ids) = validate_1998(&key_tool, "FHM34-BV66P-RR7WB-JPWD4-VFMHP");
+    assert_eq!(
+        product_key,
+        ProductKey1998 {
+            upgrade: true,
+            channel_id: "640".to_string(),
+            sequence: "123456".to_string(),
+            hash: "163921736".to_string(),
+            signature: "7458280816627942".to_string(),
+        }
+    );
+    assert_eq!(bink_ids, HashMap::from([("Test 1998".to_string(), BINK_1998)]));
+
+    let (product_key, _) = validate_1998(&key_tool, "BGRWJ-34M7T-KD694-P3DHV-8CYB6");
+    assert_eq!(product_key.sequence, "130314");
+    assert_eq!(product_key.hash, "64775384");
+    assert_eq!(product_key.signature, "432185417630461");
+}
+
+#[test]
+fn validate_key_bink2002_known_fields() {
+    let key_tool = key_tool();
+    let (product_key, bink_ids) = validate_2002(&key_tool, "BBBDH-QHWWQ-7BXVB-CBVXV-DJYB9");
+    assert_eq!(
+        product_key,
+        ProductKey2002 {
+            upgrade: true,
+            channel_id: "11".to_string(),
+            hash: "2088066657".to_string(),
+            signature: "48833849034990075".to_string(),
+            auth_info: "0".to_string(),
+        }
+    );
+    assert_eq!(bink_ids, HashMap::from([("Test 2002".to_string(), BINK_2002)]));
+
+    let (product_key, _) = validate_2002(&key_tool, "XVQ8C-C8TCM-QYCWT-TQRKC-423V8");
+    assert_eq!(product_key.channel_id, "999");
+    assert_eq!(product_key.auth_info, "1023");
+    assert_eq!(product_key.hash, "202323757");
+    assert_eq!(product_key.signature, "1103643233453162864");
+}
+
+#[test]
+fn validate_key_accepts_keys_without_dashes() {
+    let key_tool = key_tool();
+    let key = key_tool.gen_key(BINK_1998, "270", "42", "", false).unwrap();
+    let (product_key, _) = validate_1998(&key_tool, &key.replace('-', ""));
+    assert_eq!(product_key.sequence, "42");
+}
+
+#[test]
+fn validate_key_rejects_invalid_keys() {
+    let key_tool = key_tool();
+    for key in ["", "not a key", "BBBBB-BBBBB-BBBBB-BBBBB-BBBBB"] {
+        assert_eq!(key_tool.validate_key(key).unwrap(), ProductKey::Invalid);
+    }
+}
+
+#[test]
+fn validate_key_rejects_tampered_keys() {
+    let key_tool = key_tool();
+    let key = key_tool.gen_key(BINK_1998, "640", "1", "", false).unwrap();
+    let first = key.chars().next().unwrap();
+    let replacement = KEY_CHARS.iter().map(|c| *c as char).find(|c| *c != first).unwrap();
+    let tampered = format!("{replacement}{}", &key[1..]);
+    assert_eq!(key_tool.validate_key(&tampered).unwrap(), ProductKey::Invalid);
+}
+
+#[test]
+fn confirmation_id_known_answer() {
+    assert_eq!(
+        KeyTool::get_confirmation_id("334481558826870862843844566221823392794862457401103810")
+            .unwrap(),
+        "110281-200130-887120-647974-697175-027544-252733"
+    );
+}
+
+#[test]
+fn confirmation_id_ignores_dashes() {
+    assert_eq!(
+        KeyTool::get_confirmation_id(
+            "334481-558826-870862-843844-566221-823392-794862-457401-103810"
+        )
+        .unwrap(),
+        KeyTool::get_confirmation_id("334481558826870862843844566221823392794862457401103810")
+            .unwrap()
+    );
+}
+
+#[test]
+fn confirmation_id_rejects_bad_installation_ids() {
+    assert!(KeyTool::get_confirmation_id("").is_err());
+    assert!(
+        KeyTool::get_confirmation_id("33448155882687086284384456622182339279486245740110381")
+            .is_err()
+    );
+    assert!(
+        KeyTool::get_confirmation_id("334481558826870862843844566221823392794862457401103811")
+            .is_err()
+    );
+}
+
+/// The toy BINKs from `testdata/keys.json` as they'd be entered on the Custom tab
+fn custom_bink(bink_id: u8) -> CustomBink {
+    let key_tool = key_tool();
+    let bink = key_tool.bink(bink_id).unwrap();
+    CustomBink {
+        name: "Custom".to_string(),
+        bink_id: format!("{bink_id:02X}"),
+        p: bink.p.to_string(),
+        a: bink.a.to_string(),
+        b: bink.b.to_string(),
+        gx: bink.g.x.to_string(),
+        gy: bink.g.y.to_string(),
+        kx: bink.public.x.to_string(),
+        ky: bink.public.y.to_string(),
+        n: bink.n.to_string(),
+        private: bink.private.as_ref().unwrap().to_string(),
+    }
+}
+
+fn custom_bink_error(bink: CustomBink) -> String {
+    match bink.key_tool() {
+        Ok(_) => panic!("expected {bink:?} to be rejected"),
+        Err(err) => err.to_string(),
+    }
+}
+
+#[test]
+fn custom_bink_accepts_valid_curves() {
+    for bink_id in [BINK_1998, BINK_2002] {
+        let key_tool = custom_bink(bink_id).key_tool().unwrap();
+        let key = key_tool.gen_key(bink_id, "640", "", "", false).unwrap();
+        assert_ne!(key_tool.validate_key(&key).unwrap(), ProductKey::Invalid);
+
+        let public_only = CustomBink {
+            private: String::new(),
+            ..custom_bink(bink_id)
+        };
+        assert!(!public_only.key_tool().unwrap().can_generate(bink_id));
+    }
+}
+
+#[test]
+fn custom_bink_rejects_bad_curves() {
+    // A multiple of 5, and 3215031751, which passes Miller-Rabin for bases 2, 3, 5 and 7
+    for p in ["14828956556373045", "3215031751"] {
+        let bink = CustomBink {
+            p: p.to_string(),
+            ..custom_bink(BINK_1998)
+        };
+        assert_eq!(custom_bink_error(bink), "p must be a prime greater than 3");
+    }
+
+    let bink = CustomBink {
+        n: "14828956481366721".to_string(),
+        ..custom_bink(BINK_1998)
+    };
+    assert_eq!(custom_bink_error(bink), "n is not the order of G");
+
+    for private in ["0", "14828956481366719"] {
+        let bink = CustomBink {
+            private: private.to_string(),
+            ..custom_bink(BINK_1998)
+        };
+        assert_eq!(
+            custom_bink_error(bink),
+            "Private key must be from 1 to n - 1"
+        );
+    }
+
+    let bink = CustomBink {
+        private: "2745703083539618".to_string(),
+        ..custom_bink(BINK_1998)
+    };
+    assert_eq!(
+        custom_bink_error(bink),
+        "Private key doesn't match the public key"
+    );
+
+    // umskt only signs for K = -kG, so the private key's negation doesn't match either
+    let bink = custom_bink(BINK_1998);
+    let negated = bink.n.parse::<BigInt>().unwrap() - bink.private.parse::<BigInt>().unwrap();
+    let bink = CustomBink {
+        private: negated.to_string(),
+        ..bink
+    };
+    assert_eq!(
+        custom_bink_error(bink),
+        "Private key doesn't match the public key"
+    );
+}
+
+#[test]
+fn validate_key_pidgen2_cd() {
+    let key_tool = key_tool();
+    match key_tool.validate_key("111-1111111").unwrap() {
+        ProductKey::Pidgen2Cd { key } => {
+            assert_eq!(key.site, "111");
+            assert_eq!(key.serial, "1111111");
+        }
+        other => panic!("expected a PIDGEN2 CD key, got {other:?}"),
+    }
+    // Banned site, digits not adding up to a multiple of 7, and a last digit of 0, 8 or 9
+    for key in ["333-1111111", "111-1111112", "111-0000000", "111-1111117a"] {
+        assert_eq!(
+            key_tool.validate_key(key).unwrap(),
+            ProductKey::Invalid,
+            "{key}"
+        );
+    }
+}
+
+#[test]
+fn validate_key_pidgen2_oem() {
+    let key_tool = key_tool();
+    match key_tool.validate_key("12397-OEM-0011113-12345").unwrap() {
+        ProductKey::Pidgen2Oem { key } => {
+            assert_eq!(key.day, "123");
+            assert_eq!(key.year, "97");
+            assert_eq!(key.serial, "0011113");
+            assert_eq!(key.random, "12345");
+        }
+        other => panic!("expected a PIDGEN2 OEM key, got {other:?}"),
+    }
+    // Day 0 and 367, year 94, a serial not starting with 0 and a failed check
+    for key in [
+        "00097-OEM-0011113-12345",
+        "36797-OEM-0011113-12345",
+        "12394-OEM-0011113-12345",
+        "12397-OEM-1000006-12345",
+        "12397-OEM-0011114-12345",
+    ] {
+        assert_eq!(
+            key_tool.validate_key(key).unwrap(),
+            ProductKey::Invalid,
+            "{key}"
+        );
+    }
+}
+
+#[test]
+fn pidgen2_rejects_bad_input() {
+    assert!(ProductKeyCd::new(Some(1000), cycle(vec![0.5])).is_err());
+    assert!(ProductKeyCd::new(Some(999), cycle(vec![0.5])).is_err());
+    assert!(ProductKeyOem::new(Some(0), None, cycle(vec![0.5])).is_err());
+    assert!(ProductKeyOem::new(Some(367), None, cycle(vec![0.5])).is_err());
+    assert!(ProductKeyOem::new(None, Some("94"), cycle(vec![0.5])).is_err());
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn bink1998_round_trip(channel in 0u32..=999, sequence in 0u32..=999_999, upgrade: bool) {
+        let key_tool = key_tool();
+        let key = key_tool
+            .gen_key(BINK_1998, &channel.to_string(), &sequence.to_string(), "", upgrade)
+            .unwrap();
+        let (product_key, bink_ids) = validate_1998(&key_tool, &key);
+        prop_assert_eq!(product_key.channel_id, channel.to_string());
+        prop_assert_eq!(product_key.sequence, sequence.to_string());
+        prop_assert_eq!(product_key.upgrade, upgrade);
+        prop_assert_eq!(bink_ids, HashMap::from([("Test 1998".to_string(), BINK_1998)]));
+    }
+
+    #[test]
+    fn bink2002_round_trip(channel in 0u32..=999, auth_info in 0u32..=MAX_AUTH_INFO, upgrade: bool) {
+        let key_tool = key_tool();
+        let key = key_tool
+            .gen_key(BINK_2002, &channel.to_string(), "", &auth_info.to_string(), upgrade)
+            .unwrap();
+        let (product_key, bink_ids) = validate_2002(&key_tool, &key);
+        prop_assert_eq!(product_key.channel_id, channel.to_string());
+        prop_assert_eq!(product_key.auth_info, auth_info.to_string());
+        prop_assert_eq!(product_key.upgrade, upgrade);
+        prop_assert_eq!(bink_ids, HashMap::from([("Test 2002".to_string(), BINK_2002)]));
+    }
+
+    #[test]
+    fn pidgen2_cd_round_trip(
+        site in proptest::option::of(0u32..=998),
+        random in proptest::collection::vec(0.0..1.0, 1..16),
+    ) {
+        prop_assume!(site.is_none_or(|site| site % 111 != 0 || site < 333));
+        let key = ProductKeyCd::new(site, cycle(random)).unwrap();
+        if let Some(site) = site {
+            prop_assert_eq!(&key.site, &format!("{site:03}"));
+        }
+        prop_assert_eq!(
+            key_tool().validate_key(&key.to_string()).unwrap(),
+            ProductKey::Pidgen2Cd { key }
+        );
+    }
+
+    #[test]
+    fn pidgen2_oem_round_trip(
+        day in proptest::option::of(1u32..=366),
+        random in proptest::collection::vec(0.0..1.0, 1..16),
+    ) {
+        let key = ProductKeyOem::new(day, None, cycle(random)).unwrap();
+        if let Some(day) = day {
+            prop_assert_eq!(&key.day, &format!("{day:03}"));
+        }
+        prop_assert_eq!(
+            key_tool().validate_key(&key.to_string()).unwrap(),
+            ProductKey::Pidgen2Oem { key }
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use crate::{
+    crypto::OEM_YEARS,
+    digital_product_id::DigitalProductId,
+    routes,
+    scanner::{self, Found},
+};
+
+/// Digits in a confirmation ID, which is eight groups of six
+const CONFIRMATION_ID_LENGTH: usize = 48;
+
+/// What a pasted value turned out to be
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Detected {
+    /// With dashes
+    Key(String),
+    /// Just the digits
+    InstallationId(String),
+    /// In groups of six
+    ConfirmationId(String),
+    /// A product ID such as `55274-640-1011873-23081`
+    ProductId {
+        product_id: String,
+        channel_id: String,
+        sequence: String,
+    },
+    /// An OEM product ID such as `55274-OEM-0011903-00102`, which is shaped like a PIDGEN2 OEM key
+    /// but has a product code where the key has its date
+    OemProductId(String),
+    DigitalProductId(DigitalProductId),
+}
+
+impl Detected {
+    pub fn describe(&self) -> String {
+        match self {
+            Detected::Key(key) => format!("Product key {key}"),
+            Detected::InstallationId(_) => "Installation ID".to_string(),
+            Detected::ConfirmationId(_) => "Confirmation ID".to_string(),
+            Detected::ProductId {
+                product_id,
+                channel_id,
+                sequence,
+            } => format!("Product ID {product_id}, channel {channel_id}, sequence {sequence}"),
+            Detected::OemProductId(product_id) => format!(
+                "OEM product ID {product_id}, which doesn't hold a channel ID or sequence to \
+                generate a key from"
+            ),
+            Detected::DigitalProductId(decoded) => format!(
+                "DigitalProductId for {}, product ID {}",
+                decoded.key, decoded.product_id
+            ),
+        }
+    }
+
+    /// Where to go to do something with it. Product IDs are for generating a matching key, so
+    /// there's nowhere to take them in validate-only builds.
+    pub fn path(&self, can_generate: bool) -> Option<String> {
+        match self {
+            Detected::Key(key) => Some(routes::validate_path(key)),
+            Detected::DigitalProductId(decoded) => Some(routes::validate_path(&decoded.key)),
+            Detected::InstallationId(installation_id) => {
+                Some(format!("/activate?iid={installation_id}"))
+            }
+            // The history is the only place a confirmation ID can be traced back from
+            Detected::ConfirmationId(confirmation_id) => {
+                Some(format!("/history?search={confirmation_id}"))
+            }
+            Detected::ProductId {
+                channel_id,
+                sequence,
+                ..
+            } => {
+                can_generate.then(|| format!("/generate?channel={channel_id}&sequence={sequence}"))
+            }
+            Detected::OemProductId(_) => None,
+        }
+    }
+}
+
+/// Works out what the whole of the text is, ignoring whitespace around it
+pub fn detect(text: &str) -> Option<Detected> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    // Keys and installation IDs are recognised the same way as when scanning, but only when
+    // they're all there is
+    let hits = scanner::scan(text);
+    if let [hit] = hits.as_slice() {
+        if hit.text == text {
+            return Some(match &hit.found {
+                Found::Key(key) => Detected::Key(key.clone()),
+                Found::InstallationId(digits) => Detected::InstallationId(digits.clone()),
+            });
+        }
+    }
+
+    pidgen2_key(text)
+        .or_else(|| confirmation_id(text))
+        .or_else(|| product_id(text))
+        .or_else(|| {
+            DigitalProductId::parse(text)
+                .ok()
+                .map(Detected::DigitalProductId)
+        })
+}
+
+/// Keys shaped like PIDGEN2 CD keys, `XXX-XXXXXXX`, or OEM keys, `DDDYY-OEM-XXXXXXX-XXXXX`.
+/// Windows XP and later show OEM product IDs in the same shape, but with a product code such as
+/// 55274 in place of the date, so the date tells them apart. A product code that happens to be a
+/// valid date, which none of Microsoft's are, would be taken for a key.
+fn pidgen2_key(text: &str) -> Option<Detected> {
+    let digits = |group: &str, length: usize| {
+        group.len() == length && group.chars().all(|c| c.is_ascii_digit())
+    };
+    match text.split('-').collect::<Vec<_>>().as_slice() {
+        [site, serial] if digits(site, 3) && digits(serial, 7) => {
+            Some(Detected::Key(text.to_string()))
+        }
+        [date, oem, serial, random]
+            if digits(date, 5)
+                && oem.eq_ignore_ascii_case("OEM")
+                && digits(serial, 7)
+                && digits(random, 5) =>
+        {
+            let (day, year) = date.split_at(3);
+            let dated = day.parse::<u32>().is_ok_and(|day| (1..=366).contains(&day))
+                && OEM_YEARS.contains(&year);
+            let text = text.to_ascii_uppercase();
+            Some(if dated {
+                Detected::Key(text)
+            } else {
+                Detected::OemProductId(text)
+            })
+        }
+        _ => None,
+    }
+}
+
+fn confirmation_id(text: &str) -> Option<Detected> {
+    if !text
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '-' | ' '))
+    {
+        return None;
+    }
+    let digits = text
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect::<Vec<_>>();
+    (digits.len() == CONFIRMATION_ID_LENGTH).then(|| {
+        Detected::ConfirmationId(
+            digits
+                .chunks(6)
+                .map(|group| group.iter().collect::<String>())
+                .collect::<Vec<_>>()
+                .join("-"),
+        )
+    })
+}
+
+/// Product IDs as Windows shows them, where the second group is the channel ID and the third
+/// is the sequence number followed by a check digit
+fn product_id(text: &str) -> Option<Detected> {
+    let groups = text.split('-').collect::<Vec<_>>();
+    let lengths_match = groups.len() == 4
+        && groups
+            .iter()
+            .zip([5, 3, 7, 5])
+            .all(|(group, length)| group.len() == length);
+    if !lengths_match
+        || !groups
+            .iter()
+            .all(|group| group.chars().all(|c| c.is_ascii_digit()))
+    {
+        return None;
+    }
+    Some(Detected::ProductId {
+        product_id: text.to_string(),
+        channel_id: groups[1].to_string(),
+        sequence: groups[2][..6].to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "FCKGW-RHQQ2-YXRKT-8TG6W-2B7Q8";
+    const INSTALLATION_ID: &str = "334481558826870862843844566221823392794862457401103810";
+
+    fn detected(text: &str) -> Detected {
+        detect(text).unwrap_or_else(|| panic!("nothing detected in {text:?}"))
+    }
+
+    #[test]
+    fn detects_keys() {
+        assert_eq!(detected(KEY), Detected::Key(KEY.to_string()));
+        assert_eq!(
+            detected(&format!("  {}\n", KEY.replace('-', "").to_lowercase())),
+            Detected::Key(KEY.to_string())
+        );
+        // Only when the key is all there is
+        assert_eq!(detect(&format!("Key: {KEY}")), None);
+    }
+
+    #[test]
+    fn detects_pidgen2_keys() {
+        assert_eq!(
+            detected("111-1111111"),
+            Detected::Key("111-1111111".to_string())
+        );
+        assert_eq!(
+            detected("12399-oem-0011903-00102"),
+            Detected::Key("12399-OEM-0011903-00102".to_string())
+        );
+    }
+
+    #[test]
+    fn detects_oem_product_ids() {
+        // Day 552 and year 74 can't be a key's date
+        let oem = detected("55274-OEM-0011903-00102");
+        assert_eq!(
+            oem,
+            Detected::OemProductId("55274-OEM-0011903-00102".to_string())
+        );
+        assert_eq!(oem.path(true), None);
+        // A real day, but not one of the years PIDGEN2 keys were made in
+        assert_eq!(
+            detected("12374-OEM-0011903-00102"),
+            Detected::OemProductId("12374-OEM-0011903-00102".to_string())
+        );
+    }
+
+    #[test]
+    fn detects_installation_ids() {
+        let installation_id =
+            detected("334481-558826-870862-843844-566221-823392-794862-457401-103810");
+        assert_eq!(
+            installation_id,
+            Detected::InstallationId(INSTALLATION_ID.to_string())
+        );
+        assert_eq!(
+            installation_id.path(false),
+            Some(format!("/activate?iid={INSTALLATION_ID}"))
+        );
+    }
+
+    #[test]
+    fn detects_confirmation_ids() {
+        let grouped = "013705-357101-211802-111624-020706-046108-302218-101008";
+        for text in [
+            grouped.to_string(),
+            grouped.replace('-', " "),
+            grouped.replace('-', ""),
+        ] {
+            assert_eq!(
+                detected(&text),
+                Detected::ConfirmationId(grouped.to_string()),
+                "{text}"
+            );
+        }
+        assert_eq!(
+            detected(grouped).path(false),
+            Some(format!("/history?search={grouped}"))
+        );
+        assert_eq!(detect(&grouped[1..]), None);
+    }
+
+    #[test]
+    fn detects_product_ids() {
+        let product_id = detected("55274-640-1011873-23081");
+        assert_eq!(
+            product_id,
+            Detected::ProductId {
+                product_id: "55274-640-1011873-23081".to_string(),
+                channel_id: "640".to_string(),
+                sequence: "101187".to_string(),
+            }
+        );
+        assert_eq!(
+            product_id.path(true),
+            Some("/generate?channel=640&sequence=101187".to_string())
+        );
+        // Validate-only builds have nothing to do with them
+        assert_eq!(product_id.path(false), None);
+        assert_eq!(detect("55274-640-101187-23081"), None);
+    }
+
+    #[test]
+    fn detects_digital_product_ids() {
+        let mut bytes = [0_u8; 0xA4];
+        bytes[0] = 0xA4;
+        bytes[8..31].copy_from_slice(b"55274-640-0000356-23002");
+        bytes[0x34..0x43].copy_from_slice(&[
+            0x46, 0x40, 0x4b, 0xcc, 0xf9, 0x45, 0xec, 0xc0, 0xe1, 0x80, 0xb8, 0xf1, 0xd5, 0xc8,
+            0x00,
+        ]);
+        let hex = bytes
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(
+            detected(&hex),
+            Detected::DigitalProductId(DigitalProductId {
+                product_id: "55274-640-0000356-23002".to_string(),
+                key: KEY.to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn detects_nothing_in_anything_else() {
+        for text in ["", "   ", "hello", "12345", "FCKGW-RHQQ2-YXRKT-8TG6W"] {
+            assert_eq!(detect(text), None, "{text:?}");
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use anyhow::{bail, Result};
+
+use crate::crypto::KEY_CHARS;
+
+/// Where the product ID string sits in the blob, as NUL-terminated ASCII
+const PRODUCT_ID_RANGE: std::ops::Range<usize> = 0x08..0x20;
+
+/// Where the key sits in the blob, as a little-endian base-24 number
+const KEY_RANGE: std::ops::Range<usize> = 0x34..0x43;
+
+/// What's recovered from a Windows XP/2000/Server 2003 `DigitalProductId` registry value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DigitalProductId {
+    pub product_id: String,
+    pub key: String,
+}
+
+impl DigitalProductId {
+    /// Decodes the value from a hex dump, a line of `reg query` output or a `.reg` export
+    pub fn parse(text: &str) -> Result<Self> {
+        Self::from_bytes(&hex_bytes(text)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < KEY_RANGE.end {
+            bail!(
+                "A DigitalProductId is at least {} bytes long, but this is only {}",
+                KEY_RANGE.end,
+                bytes.len()
+            );
+        }
+
+        let product_id = bytes[PRODUCT_ID_RANGE]
+            .iter()
+            .take_while(|byte| **byte != 0)
+            .map(|byte| *byte as char)
+            .collect::<String>();
+
+        let mut number = bytes[KEY_RANGE].to_vec();
+        let mut key = [0_u8; 25];
+        // Divide the whole number by 24 over and over, the remainders being the digits from last
+        // to first
+        for digit in key.iter_mut().rev() {
+            let mut remainder = 0_u32;
+            for byte in number.iter_mut().rev() {
+                let value = (remainder << 8) | *byte as u32;
+                *byte = (value / 24) as u8;
+                remainder = value % 24;
+            }
+            *digit = KEY_CHARS[remainder as usize];
+        }
+        let key = key
+            .chunks(5)
+            .map(|group| String::from_utf8_lossy(group))
+            .collect::<Vec<_>>()
+            .join("-");
+
+        Ok(Self { product_id, key })
+    }
+}
+
+/// Pulls the bytes out of whatever form the value was copied in
+fn hex_bytes(text: &str) -> Result<Vec<u8>> {
+    // .reg exports are UTF-16, which comes through as text with NULs between the characters
+    let text = text.replace(['\0', '\u{feff}', '\u{fffd}'], "");
+
+    let lower = text.to_ascii_lowercase();
+    let hex = if let Some(start) = lower.find("\"digitalproductid\"=hex:") {
+        // A .reg value carries on over lines ending in a backslash
+        let value = &text[start + "\"digitalproductid\"=hex:".len()..];
+        let mut hex = String::new();
+        for line in value.lines() {
+            let line = line.trim();
+            hex.push_str(line.trim_end_matches('\\'));
+            if !line.ends_with('\\') {
+                break;
+            }
+        }
+        hex
+    } else if let Some(line) = text.lines().find(|line| line.contains("REG_BINARY")) {
+        line.split_whitespace()
+            .last()
+            .unwrap_or_default()
+            .to_string()
+    } else {
+        text.replace("0x", "").replace("0X", "")
+    };
+
+    let digits = hex
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, ',' | ':' | '-' | '\\'))
+        .collect::<Vec<_>>();
+    if let Some(c) = digits.iter().find(|c| !c.is_ascii_hexdigit()) {
+        bail!("'{c}' isn't a hex digit");
+    }
+    if digits.is_empty() || digits.len() % 2 != 0 {
+        bail!("Expected pairs of hex digits");
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| {
+            let pair = pair.iter().collect::<String>();
+            u8::from_str_radix(&pair, 16).unwrap_or_default()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 164-byte value laid out as Windows XP writes it, holding the well-known XP VLK key
+    /// FCKGW-RHQQ2-YXRKT-8TG6W-2B7Q8 and a product ID that matches its channel and sequence
+    const XP_VALUE: &str = "\
+        a4 00 00 00 03 00 00 00 35 35 32 37 34 2d 36 34
+        30 2d 30 30 30 30 33 35 36 2d 32 33 30 30 32 00
+        00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+        00 00 00 00 46 40 4b cc f9 45 ec c0 e1 80 b8 f1
+        d5 c8 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+        00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+        00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+        00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+        00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+        00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+        00 00 00 00";
+
+    const XP_KEY: &str = "FCKGW-RHQQ2-YXRKT-8TG6W-2B7Q8";
+    const XP_PRODUCT_ID: &str = "55274-640-0000356-23002";
+
+    fn xp_bytes() -> Vec<u8> {
+        hex_bytes(XP_VALUE).unwrap()
+    }
+
+    /// A value with only the key's bytes set
+    fn with_key(key: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0; KEY_RANGE.end];
+        bytes[KEY_RANGE.start..KEY_RANGE.start + key.len()].copy_from_slice(key);
+        bytes
+    }
+
+    fn key_of(bytes: &[u8]) -> String {
+        DigitalProductId::from_bytes(bytes).unwrap().key
+    }
+
+    #[test]
+    fn decodes_an_xp_value() {
+        assert_eq!(xp_bytes().len(), 0xA4);
+        assert_eq!(
+            DigitalProductId::parse(XP_VALUE).unwrap(),
+            DigitalProductId {
+                product_id: XP_PRODUCT_ID.to_string(),
+                key: XP_KEY.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_base_24() {
+        assert_eq!(key_of(&with_key(&[])), "BBBBB-BBBBB-BBBBB-BBBBB-BBBBB");
+        assert_eq!(key_of(&with_key(&[1])), "BBBBB-BBBBB-BBBBB-BBBBB-BBBBC");
+        assert_eq!(key_of(&with_key(&[23])), "BBBBB-BBBBB-BBBBB-BBBBB-BBBB9");
+        assert_eq!(key_of(&with_key(&[24])), "BBBBB-BBBBB-BBBBB-BBBBB-BBBCB");
+        // 24^2 + 2 * 24 + 3 = 627, little-endian
+        assert_eq!(
+            key_of(&with_key(&[0x73, 0x02])),
+            "BBBBB-BBBBB-BBBBB-BBBBB-BBCDF"
+        );
+    }
+
+    #[test]
+    fn reads_the_key_from_0x34_to_0x43() {
+        let mut bytes = xp_bytes();
+        // Neither neighbouring byte is part of the key
+        bytes[KEY_RANGE.start - 1] = 0xFF;
+        bytes[KEY_RANGE.end] = 0xFF;
+        assert_eq!(key_of(&bytes), XP_KEY);
+        bytes[KEY_RANGE.start] ^= 1;
+        assert_ne!(key_of(&bytes), XP_KEY);
+
+        let short = &xp_bytes()[..KEY_RANGE.end - 1];
+        assert!(DigitalProductId::from_bytes(short).is_err());
+        assert_eq!(key_of(&xp_bytes()[..KEY_RANGE.end]), XP_KEY);
+    }
+
+    #[test]
+    fn parses_reg_exports() {
+        // regedit writes 25 bytes to a line, carrying on with a backslash
+        let hex = xp_bytes()
+            .chunks(25)
+            .map(|line| {
+                line.iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect::<Vec<_>>()
+            .join(",\\\r\n  ");
+        let export = format!(
+            "Windows Registry Editor Version 5.00\r\n\r\n\
+            [HKEY_LOCAL_MACHINE\\SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion]\r\n\
+            \"ProductId\"=\"{XP_PRODUCT_ID}\"\r\n\
+            \"DigitalProductId\"=hex:{hex}\r\n\
+            \"LicenseInfo\"=hex:01,02,03\r\n"
+        );
+        assert_eq!(hex_bytes(&export).unwrap(), xp_bytes());
+
+        // As UTF-16, with a byte order mark and NULs between the characters
+        let utf16 = std::iter::once('\u{feff}')
+            .chain(export.chars().flat_map(|c| [c, '\0']))
+            .collect::<String>();
+        assert_eq!(hex_bytes(&utf16).unwrap(), xp_bytes());
+    }
+
+    #[test]
+    fn parses_reg_query_output() {
+        let hex = XP_VALUE
+            .split_whitespace()
+            .collect::<String>()
+            .to_uppercase();
+        let output = format!(
+            "\r\nHKEY_LOCAL_MACHINE\\SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion\r\n    \
+            DigitalProductId    REG_BINARY    {hex}\r\n\r\n"
+        );
+        assert_eq!(hex_bytes(&output).unwrap(), xp_bytes());
+    }
+
+    #[test]
+    fn parses_hex_dumps() {
+        assert_eq!(
+            hex_bytes("0xA4, 0x00 0X03:ff").unwrap(),
+            [0xA4, 0x00, 0x03, 0xFF]
+        );
+        assert_eq!(hex_bytes("a4-00-03").unwrap(), [0xA4, 0x00, 0x03]);
+        assert!(hex_bytes("").is_err());
+        assert!(hex_bytes("a4 0").is_err());
+        assert!(hex_bytes("a4 zz").is_err());
+    }
+}
@@ -0,0 +1,228 @@
+use std::time::Duration;
+
+use leptos::{event_target, set_timeout};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, BlobPropertyBag, Event, HtmlAnchorElement, HtmlInputElement, Url};
+
+/// Offers text to the user as a file download
+pub fn download(filename: &str, mime: &str, contents: &str) {
+    let options = BlobPropertyBag::new();
+    options.set_type(mime);
+    let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+    if let Ok(blob) = Blob::new_with_str_sequence_and_options(&parts, &options) {
+        save_blob(filename, &blob);
+    }
+}
+
+/// Offers binary data to the user as a file download
+pub fn download_bytes(filename: &str, mime: &str, contents: &[u8]) {
+    let options = BlobPropertyBag::new();
+    options.set_type(mime);
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(contents));
+    if let Ok(blob) = Blob::new_with_u8_array_sequence_and_options(&parts, &options) {
+        save_blob(filename, &blob);
+    }
+}
+
+fn save_blob(filename: &str, blob: &Blob) -> Option<()> {
+    let url = Url::create_object_url_with_blob(blob).ok()?;
+    let anchor = web_sys::window()?
+        .document()?
+        .create_element("a")
+        .ok()?
+        .dyn_into::<HtmlAnchorElement>()
+        .ok()?;
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+    // Some browsers only start the download after the click has been handled, so the URL has to
+    // outlive it
+    set_timeout(
+        move || {
+            let _ = Url::revoke_object_url(&url);
+        },
+        Duration::from_secs(1),
+    );
+    Some(())
+}
+
+/// Reads the file picked in a file input as text
+pub async fn read_text(ev: &Event) -> Option<String> {
+    let input = event_target::<HtmlInputElement>(ev);
+    let file = input.files()?.get(0)?;
+    // Clear the input so picking the same file again still fires an event
+    input.set_value("");
+    JsFuture::from(file.text()).await.ok()?.as_string()
+}
+
+/// Reads every file picked in a file input as text, along with their names
+pub async fn read_all_text(ev: &Event) -> Vec<(String, String)> {
+    let input = event_target::<HtmlInputElement>(ev);
+    let Some(list) = input.files() else {
+        return Vec::new();
+    };
+    let files = (0..list.length())
+        .filter_map(|i| list.get(i))
+        .collect::<Vec<_>>();
+    input.set_value("");
+    let mut contents = Vec::new();
+    for file in files {
+        if let Ok(text) = JsFuture::from(file.text()).await {
+            contents.push((file.name(), text.as_string().unwrap_or_default()));
+        }
+    }
+    contents
+}
+
+/// The CRC-32 that ZIP files use to check their contents
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0_u32, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB88320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+/// Packs text files into an uncompressed ZIP archive, for downloading many files at once
+pub fn zip(files: &[(String, String)]) -> Vec<u8> {
+    // 1 January 1980, the earliest date a ZIP file can hold
+    const DATE: u16 = 0x21;
+    // Names are UTF-8
+    const FLAGS: u16 = 0x0800;
+
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for (name, contents) in files {
+        let offset = archive.len() as u32;
+        let crc = crc32(contents.as_bytes());
+        let size = contents.len() as u32;
+
+        archive.extend(0x04034b50_u32.to_le_bytes());
+        for field in [20, FLAGS, 0, 0, DATE] {
+            archive.extend(field.to_le_bytes());
+        }
+        for field in [crc, size, size] {
+            archive.extend(field.to_le_bytes());
+        }
+        archive.extend((name.len() as u16).to_le_bytes());
+        archive.extend(0_u16.to_le_bytes());
+        archive.extend(name.as_bytes());
+        archive.extend(contents.as_bytes());
+
+        directory.extend(0x02014b50_u32.to_le_bytes());
+        for field in [20, 20, FLAGS, 0, 0, DATE] {
+            directory.extend(field.to_le_bytes());
+        }
+        for field in [crc, size, size] {
+            directory.extend(field.to_le_bytes());
+        }
+        for field in [name.len() as u16, 0, 0, 0, 0] {
+            directory.extend(field.to_le_bytes());
+        }
+        for field in [0, offset] {
+            directory.extend(field.to_le_bytes());
+        }
+        directory.extend(name.as_bytes());
+    }
+
+    let directory_offset = archive.len() as u32;
+    let count = files.len() as u16;
+    archive.extend(&directory);
+    archive.extend(0x06054b50_u32.to_le_bytes());
+    for field in [0, 0, count, count] {
+        archive.extend(field.to_le_bytes());
+    }
+    for field in [directory.len() as u32, directory_offset] {
+        archive.extend(field.to_le_bytes());
+    }
+    archive.extend(0_u16.to_le_bytes());
+    archive
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16_at(bytes: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes([bytes[at], bytes[at + 1]])
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn crc32_matches_the_standard_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn zips_files() {
+        let files = [
+            ("a.txt".to_string(), "hello".to_string()),
+            ("dir/b.sif".to_string(), "".to_string()),
+        ];
+        let archive = zip(&files);
+
+        // Walk the local headers, checking each holds its file as it is
+        let mut offsets = Vec::new();
+        let mut at = 0;
+        for (name, contents) in &files {
+            offsets.push(at as u32);
+            assert_eq!(u32_at(&archive, at), 0x04034b50);
+            assert_eq!(u16_at(&archive, at + 4), 20);
+            assert_eq!(u16_at(&archive, at + 6), 0x0800);
+            // Stored, not compressed
+            assert_eq!(u16_at(&archive, at + 8), 0);
+            assert_eq!(u32_at(&archive, at + 14), crc32(contents.as_bytes()));
+            assert_eq!(u32_at(&archive, at + 18), contents.len() as u32);
+            assert_eq!(u32_at(&archive, at + 22), contents.len() as u32);
+            assert_eq!(u16_at(&archive, at + 26) as usize, name.len());
+            assert_eq!(u16_at(&archive, at + 28), 0);
+            let name_at = at + 30;
+            assert_eq!(&archive[name_at..name_at + name.len()], name.as_bytes());
+            let contents_at = name_at + name.len();
+            at = contents_at + contents.len();
+            assert_eq!(&archive[contents_at..at], contents.as_bytes());
+        }
+
+        // The end record says where the central directory is
+        let end = archive.len() - 22;
+        assert_eq!(u32_at(&archive, end), 0x06054b50);
+        assert_eq!(u16_at(&archive, end + 8), 2);
+        assert_eq!(u16_at(&archive, end + 10), 2);
+        assert_eq!(u32_at(&archive, end + 12) as usize, end - at);
+        assert_eq!(u32_at(&archive, end + 16) as usize, at);
+        assert_eq!(u16_at(&archive, end + 20), 0);
+
+        // Which has an entry pointing at each local header
+        for ((name, contents), offset) in files.iter().zip(offsets) {
+            assert_eq!(u32_at(&archive, at), 0x02014b50);
+            assert_eq!(u16_at(&archive, at + 8), 0x0800);
+            assert_eq!(u16_at(&archive, at + 10), 0);
+            assert_eq!(u32_at(&archive, at + 16), crc32(contents.as_bytes()));
+            assert_eq!(u32_at(&archive, at + 20), contents.len() as u32);
+            assert_eq!(u32_at(&archive, at + 24), contents.len() as u32);
+            assert_eq!(u16_at(&archive, at + 28) as usize, name.len());
+            assert_eq!(u32_at(&archive, at + 42), offset);
+            let name_at = at + 46;
+            assert_eq!(&archive[name_at..name_at + name.len()], name.as_bytes());
+            at = name_at + name.len();
+        }
+        assert_eq!(at, end);
+    }
+
+    #[test]
+    fn zips_nothing() {
+        let archive = zip(&[]);
+        assert_eq!(archive.len(), 22);
+        assert_eq!(u32_at(&archive, 0), 0x06054b50);
+        assert!(archive[4..].iter().all(|byte| *byte == 0));
+    }
+}
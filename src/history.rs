@@ -0,0 +1,322 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    Event, IdbDatabase, IdbObjectStore, IdbObjectStoreParameters, IdbOpenDbRequest, IdbRequest,
+    IdbTransactionMode,
+};
+
+use crate::crypto::ProductKey;
+
+const DATABASE: &str = "mskey-web";
+const DATABASE_VERSION: u32 = 1;
+const STORE: &str = "history";
+
+/// Something that was done with the app, as it's recorded in the history
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum HistoryItem {
+    Generated {
+        key: String,
+        product: String,
+        bink: String,
+        channel: String,
+        sequence: String,
+        upgrade: bool,
+    },
+    Validated {
+        key: String,
+        valid: bool,
+        products: Vec<String>,
+        channel: Option<String>,
+    },
+    Activated {
+        installation_id: String,
+        confirmation_id: String,
+    },
+}
+
+impl HistoryItem {
+    pub fn validated(key: &str, result: &ProductKey) -> Self {
+        let (mut products, channel) = match result {
+            ProductKey::Bink1998 { key, bink_ids, .. } => (
+                bink_ids.keys().cloned().collect::<Vec<_>>(),
+                Some(key.channel_id.clone()),
+            ),
+            ProductKey::Bink2002 { key, bink_ids, .. } => (
+                bink_ids.keys().cloned().collect::<Vec<_>>(),
+                Some(key.channel_id.clone()),
+            ),
+            // PIDGEN2 keys aren't tied to particular products and have no channel
+            ProductKey::Pidgen2Cd { .. }
+            | ProductKey::Pidgen2Oem { .. }
+            | ProductKey::Invalid
+            | ProductKey::Empty => (Vec::new(), None),
+        };
+        products.sort();
+        HistoryItem::Validated {
+            key: key.trim().to_string(),
+            valid: !matches!(result, ProductKey::Invalid | ProductKey::Empty),
+            products,
+            channel,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            HistoryItem::Generated { .. } => "Generated",
+            HistoryItem::Validated { .. } => "Validated",
+            HistoryItem::Activated { .. } => "Activated",
+        }
+    }
+
+    pub fn products(&self) -> Vec<String> {
+        match self {
+            HistoryItem::Generated { product, .. } => vec![product.clone()],
+            HistoryItem::Validated { products, .. } => products.clone(),
+            HistoryItem::Activated { .. } => Vec::new(),
+        }
+    }
+
+    pub fn channel(&self) -> Option<&str> {
+        match self {
+            HistoryItem::Generated { channel, .. } => Some(channel),
+            HistoryItem::Validated { channel, .. } => channel.as_deref(),
+            HistoryItem::Activated { .. } => None,
+        }
+    }
+
+    /// The key or ID the entry is about
+    pub fn value(&self) -> String {
+        match self {
+            HistoryItem::Generated { key, .. } | HistoryItem::Validated { key, .. } => key.clone(),
+            HistoryItem::Activated {
+                installation_id, ..
+            } => installation_id.clone(),
+        }
+    }
+
+    /// Everything else worth knowing about the entry, in a line
+    pub fn details(&self) -> String {
+        match self {
+            // PIDGEN2 keys have no BINK
+            HistoryItem::Generated { bink, .. } if bink.is_empty() => "PIDGEN2".to_string(),
+            HistoryItem::Generated {
+                bink,
+                sequence,
+                upgrade,
+                ..
+            } => {
+                let sequence = if sequence.is_empty() { "random" } else { sequence };
+                format!("BINK {bink}, sequence {sequence}, upgrade {upgrade}")
+            }
+            HistoryItem::Validated { valid: true, .. } => "Valid".to_string(),
+            HistoryItem::Validated { valid: false, .. } => "Invalid".to_string(),
+            HistoryItem::Activated {
+                confirmation_id, ..
+            } => format!("Confirmation ID {confirmation_id}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Assigned by IndexedDB when the entry is added
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u32>,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub item: HistoryItem,
+}
+
+impl HistoryEntry {
+    fn now(item: HistoryItem) -> Self {
+        Self {
+            id: None,
+            timestamp: js_sys::Date::now() as u64,
+            item,
+        }
+    }
+
+    fn date(&self) -> js_sys::Date {
+        js_sys::Date::new(&JsValue::from_f64(self.timestamp as f64))
+    }
+
+    /// The time in the user's locale
+    pub fn time(&self) -> String {
+        self.date()
+            .to_locale_string("default", &JsValue::UNDEFINED)
+            .into()
+    }
+
+    /// Checks whether the entry's key, IDs or products contain the search text
+    pub fn matches(&self, search: &str) -> bool {
+        let search = search.trim().to_lowercase();
+        search.is_empty()
+            || self.item.value().to_lowercase().contains(&search)
+            || self.item.details().to_lowercase().contains(&search)
+            || self
+                .item
+                .products()
+                .iter()
+                .any(|product| product.to_lowercase().contains(&search))
+    }
+}
+
+/// Adds an item to the history. Failing to record something shouldn't get in the way of
+/// whatever was being done, so errors are only logged.
+pub fn record(item: HistoryItem) {
+    record_all(vec![item]);
+}
+
+pub fn record_all(items: Vec<HistoryItem>) {
+    if items.is_empty() {
+        return;
+    }
+    leptos::spawn_local(async move {
+        let result = async {
+            let store = store(IdbTransactionMode::Readwrite).await?;
+            let mut last = None;
+            for item in items {
+                let entry = to_js(&HistoryEntry::now(item))?;
+                last = Some(store.add(&entry).map_err(js_error)?);
+            }
+            if let Some(last) = last {
+                request(&last).await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        };
+        if let Err(err) = result.await {
+            leptos::logging::warn!("Couldn't record history: {err}");
+        }
+    });
+}
+
+/// Every entry in the history, newest first
+pub async fn load() -> Result<Vec<HistoryEntry>> {
+    let store = store(IdbTransactionMode::Readonly).await?;
+    let entries = request(&store.get_all().map_err(js_error)?).await?;
+    let mut entries = js_sys::Array::from(&entries)
+        .iter()
+        .map(|entry| from_js(&entry))
+        .collect::<Result<Vec<_>>>()?;
+    entries.reverse();
+    Ok(entries)
+}
+
+pub async fn delete(id: u32) -> Result<()> {
+    let store = store(IdbTransactionMode::Readwrite).await?;
+    request(&store.delete(&JsValue::from(id)).map_err(js_error)?).await?;
+    Ok(())
+}
+
+pub async fn clear() -> Result<()> {
+    let store = store(IdbTransactionMode::Readwrite).await?;
+    request(&store.clear().map_err(js_error)?).await?;
+    Ok(())
+}
+
+pub fn to_json(entries: &[HistoryEntry]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(entries)?)
+}
+
+pub fn to_csv(entries: &[HistoryEntry]) -> String {
+    let mut csv = "id,time,kind,products,channel,value,details\r\n".to_string();
+    for entry in entries {
+        let row = [
+            entry.id.map(|id| id.to_string()).unwrap_or_default(),
+            String::from(entry.date().to_iso_string()),
+            entry.item.kind().to_string(),
+            entry.item.products().join("; "),
+            entry.item.channel().unwrap_or_default().to_string(),
+            entry.item.value(),
+            entry.item.details(),
+        ];
+        let row = row.iter().map(|field| csv_field(field)).collect::<Vec<_>>();
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Quotes a CSV field if it needs it
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+async fn open() -> Result<IdbDatabase> {
+    let factory = web_sys::window()
+        .ok_or(anyhow!("No window"))?
+        .indexed_db()
+        .map_err(js_error)?
+        .ok_or(anyhow!("IndexedDB is not available"))?;
+    let open: IdbOpenDbRequest = factory
+        .open_with_u32(DATABASE, DATABASE_VERSION)
+        .map_err(js_error)?;
+
+    let upgrade_request = open.clone();
+    let on_upgrade_needed = Closure::once_into_js(move |_: Event| {
+        let Ok(db) = upgrade_request.result() else {
+            return;
+        };
+        let db = db.unchecked_into::<IdbDatabase>();
+        let params = IdbObjectStoreParameters::new();
+        params.set_key_path(&JsValue::from_str("id"));
+        params.set_auto_increment(true);
+        let _ = db.create_object_store_with_optional_parameters(STORE, &params);
+    });
+    open.set_onupgradeneeded(Some(on_upgrade_needed.unchecked_ref()));
+
+    Ok(request(&open).await?.unchecked_into())
+}
+
+async fn store(mode: IdbTransactionMode) -> Result<IdbObjectStore> {
+    let db = open().await?;
+    let transaction = db
+        .transaction_with_str_and_mode(STORE, mode)
+        .map_err(js_error)?;
+    transaction.object_store(STORE).map_err(js_error)
+}
+
+/// Waits for an IndexedDB request to finish, returning its result
+async fn request(request: &IdbRequest) -> Result<JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let success_request = request.clone();
+        let on_success = Closure::once_into_js(move |_: Event| {
+            let result = success_request.result().unwrap_or(JsValue::UNDEFINED);
+            let _ = resolve.call1(&JsValue::NULL, &result);
+        });
+        let error_request = request.clone();
+        let on_error = Closure::once_into_js(move |_: Event| {
+            let error = error_request
+                .error()
+                .ok()
+                .flatten()
+                .map(JsValue::from)
+                .unwrap_or(JsValue::UNDEFINED);
+            let _ = reject.call1(&JsValue::NULL, &error);
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    JsFuture::from(promise).await.map_err(js_error)
+}
+
+fn to_js(entry: &HistoryEntry) -> Result<JsValue> {
+    js_sys::JSON::parse(&serde_json::to_string(entry)?).map_err(js_error)
+}
+
+fn from_js(value: &JsValue) -> Result<HistoryEntry> {
+    let json: String = js_sys::JSON::stringify(value).map_err(js_error)?.into();
+    Ok(serde_json::from_str(&json)?)
+}
+
+fn js_error(err: JsValue) -> anyhow::Error {
+    anyhow!("{err:?}")
+}
@@ -0,0 +1,322 @@
+use std::future::Future;
+
+use futures::{
+    stream::{self, AbortHandle},
+    Stream, StreamExt,
+};
+use leptos::*;
+
+use crate::{
+    crypto::ProductKey,
+    worker::{self, GenKeyRequest, Request, Response},
+};
+
+/// A long-running operation made up of many independent items
+#[derive(Clone, Debug)]
+pub enum Job {
+    Generate { request: GenKeyRequest, count: usize },
+    Validate(Vec<String>),
+    ConfirmationIds(Vec<String>),
+}
+
+impl Job {
+    fn len(&self) -> usize {
+        match self {
+            Job::Generate { count, .. } => *count,
+            Job::Validate(keys) => keys.len(),
+            Job::ConfirmationIds(installation_ids) => installation_ids.len(),
+        }
+    }
+
+    /// The worker request for each item, in order
+    fn requests(self) -> Vec<Request> {
+        match self {
+            Job::Generate { request, count } => vec![Request::Generate(request); count],
+            Job::Validate(keys) => keys.into_iter().map(Request::Validate).collect(),
+            Job::ConfirmationIds(installation_ids) => installation_ids
+                .into_iter()
+                .map(Request::ConfirmationId)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JobOutput {
+    Key(String),
+    Validation {
+        key: String,
+        result: ProductKey,
+    },
+    ConfirmationId {
+        installation_id: String,
+        confirmation_id: String,
+    },
+}
+
+/// The result of a single item of a job, streamed back as soon as it's done
+#[derive(Clone, Debug)]
+pub struct JobUpdate {
+    pub index: usize,
+    pub result: Result<JobOutput, String>,
+}
+
+/// What an item of a job came out as, given the worker's response to its request
+fn output(request: Request, response: Option<Response>) -> Result<JobOutput, String> {
+    match (request, response) {
+        (Request::Generate(_), Some(Response::Generate(key))) => key.map(JobOutput::Key),
+        (Request::Validate(key), Some(Response::Validate(result))) => {
+            Ok(JobOutput::Validation { key, result })
+        }
+        (Request::ConfirmationId(installation_id), Some(Response::ConfirmationId(result))) => {
+            result.map(|confirmation_id| JobOutput::ConfirmationId {
+                installation_id,
+                confirmation_id,
+            })
+        }
+        _ => Err("The key worker didn't respond".to_string()),
+    }
+}
+
+/// Sends the requests off through `send`, keeping up to `concurrency` of them in flight, and
+/// streams back each item's result as soon as it's done. Nothing more is sent once the stream is
+/// dropped.
+fn run<F, Fut>(requests: Vec<Request>, concurrency: usize, send: F) -> impl Stream<Item = JobUpdate>
+where
+    F: Fn(Request) -> Fut,
+    Fut: Future<Output = Option<Response>>,
+{
+    stream::iter(requests.into_iter().enumerate())
+        .map(move |(index, request)| {
+            let response = send(request.clone());
+            async move {
+                JobUpdate {
+                    index,
+                    result: output(request, response.await),
+                }
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JobStatus {
+    Idle,
+    Running,
+    Finished,
+    Cancelled,
+    Failed(String),
+}
+
+/// Reactive state of a job, cheap to copy into closures and components
+#[derive(Clone, Copy)]
+pub struct JobHandle {
+    pub status: RwSignal<JobStatus>,
+    pub done: RwSignal<usize>,
+    pub total: RwSignal<usize>,
+    pub results: RwSignal<Vec<JobUpdate>>,
+    abort: StoredValue<Option<AbortHandle>>,
+}
+
+pub fn create_job() -> JobHandle {
+    JobHandle {
+        status: create_rw_signal(JobStatus::Idle),
+        done: create_rw_signal(0),
+        total: create_rw_signal(0),
+        results: create_rw_signal(Vec::new()),
+        abort: store_value(None),
+    }
+}
+
+impl JobHandle {
+    /// Starts a job on the shared worker pool, cancelling any job that's already running
+    pub fn start(self, job: Job) {
+        self.cancel();
+
+        self.total.set(job.len());
+        self.done.set(0);
+        self.results.set(Vec::new());
+
+        let pool = match worker::pool() {
+            Ok(pool) => pool,
+            Err(err) => {
+                self.status.set(JobStatus::Failed(err));
+                return;
+            }
+        };
+        // Two items per worker keeps each one busy while the page handles the last result
+        let concurrency = pool.size() * 2;
+        let updates = run(job.requests(), concurrency, move |request| {
+            let pool = pool.clone();
+            async move { pool.request(request).await }
+        });
+        let (mut updates, abort) = stream::abortable(updates);
+        self.abort.set_value(Some(abort));
+        self.status.set(JobStatus::Running);
+
+        spawn_local(async move {
+            while let Some(update) = updates.next().await {
+                self.results.update(|results| results.push(update));
+                self.done.update(|done| *done += 1);
+            }
+            if !updates.is_aborted() {
+                self.abort.set_value(None);
+                self.status.set(JobStatus::Finished);
+            }
+        });
+    }
+
+    /// Stops the running job; results received so far are kept. No more of its items are sent to
+    /// the workers, so they're only busy with the few already in flight.
+    pub fn cancel(self) {
+        if let Some(abort) = self.abort.get_value() {
+            abort.abort();
+            self.abort.set_value(None);
+            self.status.set(JobStatus::Cancelled);
+        }
+    }
+
+    pub fn is_running(self) -> bool {
+        self.status.get() == JobStatus::Running
+    }
+
+    /// The outputs of the items that have succeeded so far, in the order they finished
+    pub fn outputs(self) -> Vec<JobOutput> {
+        self.results.with(|results| {
+            results
+                .iter()
+                .filter_map(|update| update.result.clone().ok())
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use futures::{executor::block_on, future, FutureExt};
+
+    use super::*;
+
+    fn generate(count: usize) -> Vec<Request> {
+        Job::Generate {
+            request: GenKeyRequest {
+                bink_id: 0x01,
+                channel_id: "640".to_string(),
+                sequence: String::new(),
+                auth_info: String::new(),
+                upgrade: false,
+            },
+            count,
+        }
+        .requests()
+    }
+
+    /// Answers every request straight away, counting how many were sent
+    fn counting(sent: Rc<Cell<usize>>) -> impl Fn(Request) -> future::Ready<Option<Response>> {
+        move |request| {
+            sent.set(sent.get() + 1);
+            future::ready(match request {
+                Request::Generate(_) => Some(Response::Generate(Ok(format!("KEY{}", sent.get())))),
+                _ => None,
+            })
+        }
+    }
+
+    #[test]
+    fn runs_every_item() {
+        let sent = Rc::new(Cell::new(0));
+        let updates = block_on(run(generate(50), 4, counting(sent.clone())).collect::<Vec<_>>());
+        assert_eq!(sent.get(), 50);
+        let mut indexes = updates
+            .iter()
+            .map(|update| update.index)
+            .collect::<Vec<_>>();
+        indexes.sort();
+        assert_eq!(indexes, (0..50).collect::<Vec<_>>());
+        assert!(updates
+            .iter()
+            .all(|update| matches!(update.result, Ok(JobOutput::Key(_)))));
+    }
+
+    #[test]
+    fn keeps_only_so_many_items_in_flight() {
+        let sent = Rc::new(Cell::new(0));
+        let send = {
+            let sent = sent.clone();
+            move |_| {
+                sent.set(sent.get() + 1);
+                future::pending::<Option<Response>>()
+            }
+        };
+        let mut updates = run(generate(1000), 8, send);
+        assert!(updates.next().now_or_never().is_none());
+        assert_eq!(sent.get(), 8);
+    }
+
+    #[test]
+    fn cancelled_jobs_stop_sending_items() {
+        let sent = Rc::new(Cell::new(0));
+        let (mut updates, abort) =
+            stream::abortable(run(generate(10_000), 4, counting(sent.clone())));
+        let received = block_on(updates.by_ref().take(10).count());
+        assert_eq!(received, 10);
+
+        abort.abort();
+        assert!(block_on(updates.next()).is_none());
+        // Only the items already in flight when the job was cancelled were sent
+        assert!(sent.get() <= 10 + 4, "{} items sent", sent.get());
+        drop(updates);
+        assert!(sent.get() <= 10 + 4, "{} items sent", sent.get());
+    }
+
+    #[test]
+    fn reports_missing_responses() {
+        let requests = vec![
+            Request::Validate("BBBBB-BBBBB-BBBBB-BBBBB-BBBBB".to_string()),
+            Request::ConfirmationId("1".to_string()),
+        ];
+        let updates = block_on(run(requests, 2, |_| future::ready(None)).collect::<Vec<_>>());
+        assert!(updates
+            .iter()
+            .all(|update| update.result == Err("The key worker didn't respond".to_string())));
+    }
+
+    #[test]
+    fn maps_responses_to_outputs() {
+        assert_eq!(
+            output(
+                Request::Validate("KEY".to_string()),
+                Some(Response::Validate(ProductKey::Invalid))
+            ),
+            Ok(JobOutput::Validation {
+                key: "KEY".to_string(),
+                result: ProductKey::Invalid,
+            })
+        );
+        assert_eq!(
+            output(
+                Request::ConfirmationId("1".to_string()),
+                Some(Response::ConfirmationId(Ok("2".to_string())))
+            ),
+            Ok(JobOutput::ConfirmationId {
+                installation_id: "1".to_string(),
+                confirmation_id: "2".to_string(),
+            })
+        );
+        assert_eq!(
+            output(
+                Request::ConfirmationId("1".to_string()),
+                Some(Response::ConfirmationId(Err("Invalid".to_string())))
+            ),
+            Err("Invalid".to_string())
+        );
+        // A response to some other request doesn't count
+        assert!(output(
+            Request::ConfirmationId("1".to_string()),
+            Some(Response::Generate(Ok("KEY".to_string())))
+        )
+        .is_err());
+    }
+}
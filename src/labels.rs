@@ -0,0 +1,692 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+/// Commercial label stock the sheets can be laid out for
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LabelTemplate {
+    #[default]
+    AveryL7160,
+    AveryL7163,
+    Avery5160,
+    Avery5163,
+}
+
+/// The geometry of a sheet of labels, in millimetres
+struct Sheet {
+    width: f64,
+    height: f64,
+    columns: usize,
+    rows: usize,
+    label_width: f64,
+    label_height: f64,
+    left: f64,
+    top: f64,
+    column_pitch: f64,
+    row_pitch: f64,
+}
+
+const A4: (f64, f64) = (210.0, 297.0);
+const LETTER: (f64, f64) = (215.9, 279.4);
+
+impl LabelTemplate {
+    pub const ALL: [LabelTemplate; 4] = [
+        LabelTemplate::AveryL7160,
+        LabelTemplate::AveryL7163,
+        LabelTemplate::Avery5160,
+        LabelTemplate::Avery5163,
+    ];
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|template| template.id() == id)
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            LabelTemplate::AveryL7160 => "l7160",
+            LabelTemplate::AveryL7163 => "l7163",
+            LabelTemplate::Avery5160 => "5160",
+            LabelTemplate::Avery5163 => "5163",
+        }
+    }
+
+    pub fn per_sheet(self) -> usize {
+        let sheet = self.sheet();
+        sheet.columns * sheet.rows
+    }
+
+    fn sheet(self) -> Sheet {
+        match self {
+            LabelTemplate::AveryL7160 => Sheet {
+                width: A4.0,
+                height: A4.1,
+                columns: 3,
+                rows: 7,
+                label_width: 63.5,
+                label_height: 38.1,
+                left: 7.2,
+                top: 15.15,
+                column_pitch: 66.0,
+                row_pitch: 38.1,
+            },
+            LabelTemplate::AveryL7163 => Sheet {
+                width: A4.0,
+                height: A4.1,
+                columns: 2,
+                rows: 7,
+                label_width: 99.1,
+                label_height: 38.1,
+                left: 4.65,
+                top: 15.15,
+                column_pitch: 101.6,
+                row_pitch: 38.1,
+            },
+            LabelTemplate::Avery5160 => Sheet {
+                width: LETTER.0,
+                height: LETTER.1,
+                columns: 3,
+                rows: 10,
+                label_width: 66.675,
+                label_height: 25.4,
+                left: 4.7625,
+                top: 12.7,
+                column_pitch: 69.85,
+                row_pitch: 25.4,
+            },
+            LabelTemplate::Avery5163 => Sheet {
+                width: LETTER.0,
+                height: LETTER.1,
+                columns: 2,
+                rows: 5,
+                label_width: 101.6,
+                label_height: 50.8,
+                left: 3.96875,
+                top: 12.7,
+                column_pitch: 104.775,
+                row_pitch: 50.8,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for LabelTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LabelTemplate::AveryL7160 => {
+                write!(f, "Avery L7160 (A4, 21 per sheet, 63.5 × 38.1 mm)")
+            }
+            LabelTemplate::AveryL7163 => {
+                write!(f, "Avery L7163 (A4, 14 per sheet, 99.1 × 38.1 mm)")
+            }
+            LabelTemplate::Avery5160 => write!(f, "Avery 5160 (Letter, 30 per sheet, 2⅝ × 1 in)"),
+            LabelTemplate::Avery5163 => write!(f, "Avery 5163 (Letter, 10 per sheet, 4 × 2 in)"),
+        }
+    }
+}
+
+/// What gets printed on a single label
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Label {
+    pub key: String,
+    pub product: String,
+    pub channel: String,
+    pub product_id: Option<String>,
+    pub machine: Option<String>,
+}
+
+/// How labels are placed on the sheets
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SheetOptions {
+    pub template: LabelTemplate,
+    /// Labels already used on the first sheet
+    pub skip: usize,
+    pub outlines: bool,
+}
+
+/// The part of the Windows product ID that a BINK1998 key determines: the channel ID, then the
+/// sequence with a check digit that makes its digits add up to a multiple of 7
+pub fn product_id(channel_id: u32, sequence: u32) -> String {
+    let digit_sum: u32 = format!("{sequence:06}")
+        .bytes()
+        .map(|digit| (digit - b'0') as u32)
+        .sum();
+    let check = (7 - digit_sum % 7) % 7;
+    format!("{channel_id:03}-{sequence:06}{check}")
+}
+
+/// Code 128 bar and space widths for each symbol value, ending with the stop pattern
+const CODE128_PATTERNS: [&str; 107] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212",
+    "221213", "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221",
+    "223211", "221132", "221231", "213212", "223112", "312131", "311222", "321122", "321221",
+    "312212", "322112", "322211", "212123", "212321", "232121", "111323", "131123", "131321",
+    "112313", "132113", "132311", "211313", "231113", "231311", "112133", "112331", "132131",
+    "113123", "113321", "133121", "313121", "211331", "231131", "213113", "213311", "213131",
+    "311123", "311321", "331121", "312113", "312311", "332111", "314111", "221411", "431111",
+    "111224", "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111", "111242",
+    "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311",
+    "113141", "114131", "311141", "411131", "211412", "211214", "211232", "2331112",
+];
+
+const CODE128_START_B: usize = 104;
+const CODE128_STOP: usize = 106;
+
+/// Blank modules either side of a barcode so scanners can find its ends
+const QUIET_ZONE: usize = 10;
+
+/// Encodes printable ASCII as Code 128 code set B, returning the bars as (start, width) runs of
+/// modules, and the total width in modules
+fn code128(text: &str) -> Option<(Vec<(usize, usize)>, usize)> {
+    let values = text
+        .bytes()
+        .map(|byte| (32..127).contains(&byte).then(|| (byte - 32) as usize))
+        .collect::<Option<Vec<_>>>()?;
+    let checksum = values
+        .iter()
+        .enumerate()
+        .fold(CODE128_START_B, |sum, (i, value)| sum + value * (i + 1))
+        % 103;
+
+    let symbols = std::iter::once(CODE128_START_B)
+        .chain(values)
+        .chain([checksum, CODE128_STOP]);
+    let mut bars = Vec::new();
+    let mut position = QUIET_ZONE;
+    for symbol in symbols {
+        for (i, width) in CODE128_PATTERNS[symbol].bytes().enumerate() {
+            let width = (width - b'0') as usize;
+            if i % 2 == 0 {
+                bars.push((position, width));
+            }
+            position += width;
+        }
+    }
+    Some((bars, position + QUIET_ZONE))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Font {
+    Regular,
+    Bold,
+    Mono,
+}
+
+/// Something drawn on a page, positioned in millimetres from the top left
+enum Mark {
+    Text {
+        x: f64,
+        y: f64,
+        size: f64,
+        font: Font,
+        text: String,
+    },
+    Bar {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+    Outline {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+}
+
+/// Everything on one label, which is clipped to its outline when drawn
+struct PlacedLabel {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    marks: Vec<Mark>,
+}
+
+fn layout_label(label: &Label, x: f64, y: f64, width: f64, height: f64) -> PlacedLabel {
+    let padding = (height * 0.08).min(3.0);
+    let title_size = (height * 0.1).clamp(2.6, 4.0);
+    let small_size = title_size * 0.75;
+    let key_size = title_size * 0.85;
+
+    let mut details = format!("Channel {}", label.channel);
+    if let Some(product_id) = &label.product_id {
+        write!(details, "   PID {product_id}").unwrap();
+    }
+    let lines = [
+        Some((title_size, Font::Bold, label.product.clone())),
+        Some((small_size, Font::Regular, details)),
+        label
+            .machine
+            .as_ref()
+            .map(|machine| (small_size, Font::Regular, format!("Machine {machine}"))),
+        Some((key_size, Font::Mono, label.key.clone())),
+    ];
+
+    let mut marks = Vec::new();
+    let mut top = padding - title_size * 0.3;
+    for (size, font, text) in lines.into_iter().flatten() {
+        top += size * 1.3;
+        marks.push(Mark::Text {
+            x: padding,
+            y: top,
+            size,
+            font,
+            text,
+        });
+    }
+
+    // Scanners don't need the dashes, and leaving them out makes the bars wider
+    if let Some((bars, modules)) = code128(&label.key.replace('-', "")) {
+        let module = (width - padding * 2.0) / modules as f64;
+        let bar_top = top + small_size * 0.6;
+        let bar_height = height - padding - bar_top;
+        if bar_height > 0.0 {
+            marks.extend(bars.into_iter().map(|(start, bar_width)| Mark::Bar {
+                x: padding + start as f64 * module,
+                y: bar_top,
+                width: bar_width as f64 * module,
+                height: bar_height,
+            }));
+        }
+    }
+
+    PlacedLabel {
+        x,
+        y,
+        width,
+        height,
+        marks,
+    }
+}
+
+/// Lays the labels out over as many sheets as they need
+fn layout(options: SheetOptions, labels: &[Label]) -> Vec<Vec<PlacedLabel>> {
+    let sheet = options.template.sheet();
+    let per_sheet = sheet.columns * sheet.rows;
+    let skip = options.skip.min(per_sheet - 1);
+
+    let mut pages: Vec<Vec<PlacedLabel>> = Vec::new();
+    for (i, label) in labels.iter().enumerate() {
+        let slot = skip + i;
+        let (page, position) = (slot / per_sheet, slot % per_sheet);
+        if pages.len() <= page {
+            pages.push(Vec::new());
+        }
+        let x = sheet.left + (position % sheet.columns) as f64 * sheet.column_pitch;
+        let y = sheet.top + (position / sheet.columns) as f64 * sheet.row_pitch;
+        let mut placed = layout_label(label, x, y, sheet.label_width, sheet.label_height);
+        if options.outlines {
+            placed.marks.push(Mark::Outline {
+                x: 0.0,
+                y: 0.0,
+                width: sheet.label_width,
+                height: sheet.label_height,
+            });
+        }
+        pages[page].push(placed);
+    }
+    pages
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Draws each sheet of labels as an SVG document at its real size
+pub fn to_svg(options: SheetOptions, labels: &[Label]) -> Vec<String> {
+    let sheet = options.template.sheet();
+    layout(options, labels)
+        .into_iter()
+        .map(|page| {
+            let mut svg = format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}mm" height="{h}mm" viewBox="0 0 {w} {h}">"#,
+                w = sheet.width,
+                h = sheet.height
+            );
+            write!(svg, r##"<rect width="{}" height="{}" fill="#fff"/>"##, sheet.width, sheet.height)
+                .unwrap();
+            for label in page {
+                // A nested viewport clips anything that doesn't fit on the label
+                write!(
+                    svg,
+                    r#"<svg x="{:.3}" y="{:.3}" width="{:.3}" height="{:.3}">"#,
+                    label.x, label.y, label.width, label.height
+                )
+                .unwrap();
+                for mark in label.marks {
+                    match mark {
+                        Mark::Text {
+                            x,
+                            y,
+                            size,
+                            font,
+                            text,
+                        } => {
+                            let font = match font {
+                                Font::Regular => r#"font-family="Helvetica, Arial, sans-serif""#,
+                                Font::Bold => {
+                                    r#"font-family="Helvetica, Arial, sans-serif" font-weight="bold""#
+                                }
+                                Font::Mono => r#"font-family="Courier New, monospace""#,
+                            };
+                            write!(
+                                svg,
+                                r#"<text x="{x:.3}" y="{y:.3}" font-size="{size:.3}" {font}>{}</text>"#,
+                                escape_xml(&text)
+                            )
+                            .unwrap();
+                        }
+                        Mark::Bar {
+                            x,
+                            y,
+                            width,
+                            height,
+                        } => write!(
+                            svg,
+                            r#"<rect x="{x:.3}" y="{y:.3}" width="{width:.3}" height="{height:.3}"/>"#
+                        )
+                        .unwrap(),
+                        Mark::Outline {
+                            x,
+                            y,
+                            width,
+                            height,
+                        } => write!(
+                            svg,
+                            r##"<rect x="{x:.3}" y="{y:.3}" width="{width:.3}" height="{height:.3}" rx="2" fill="none" stroke="#999" stroke-width="0.2"/>"##
+                        )
+                        .unwrap(),
+                    }
+                }
+                svg.push_str("</svg>");
+            }
+            svg.push_str("</svg>");
+            svg
+        })
+        .collect()
+}
+
+/// Points per millimetre, PDF's unit of length
+const PT: f64 = 72.0 / 25.4;
+
+/// The standard fonts every PDF reader has, so nothing needs embedding
+const PDF_FONTS: [(&str, &str); 3] = [
+    ("F1", "Helvetica"),
+    ("F2", "Helvetica-Bold"),
+    ("F3", "Courier"),
+];
+
+/// Text as a PDF string literal. Anything outside printable ASCII is replaced, which keeps the
+/// whole file plain text.
+fn escape_pdf(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '(' | ')' | '\\' => format!("\\{c}"),
+            ' '..='~' => c.to_string(),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
+
+/// Draws the sheets of labels as a PDF with one page per sheet. The output is plain ASCII.
+pub fn to_pdf(options: SheetOptions, labels: &[Label]) -> String {
+    let sheet = options.template.sheet();
+    let page_height = sheet.height * PT;
+
+    let contents = layout(options, labels)
+        .into_iter()
+        .map(|page| {
+            let mut stream = String::new();
+            for label in page {
+                let (left, top) = (label.x * PT, page_height - label.y * PT);
+                writeln!(
+                    stream,
+                    "q {:.2} {:.2} {:.2} {:.2} re W n",
+                    left,
+                    top - label.height * PT,
+                    label.width * PT,
+                    label.height * PT
+                )
+                .unwrap();
+                for mark in label.marks {
+                    match mark {
+                        Mark::Text {
+                            x,
+                            y,
+                            size,
+                            font,
+                            text,
+                        } => {
+                            let font = match font {
+                                Font::Regular => "F1",
+                                Font::Bold => "F2",
+                                Font::Mono => "F3",
+                            };
+                            writeln!(
+                                stream,
+                                "BT /{font} {:.2} Tf {:.2} {:.2} Td ({}) Tj ET",
+                                size * PT,
+                                left + x * PT,
+                                top - y * PT,
+                                escape_pdf(&text)
+                            )
+                            .unwrap();
+                        }
+                        Mark::Bar {
+                            x,
+                            y,
+                            width,
+                            height,
+                        } => writeln!(
+                            stream,
+                            "{:.3} {:.3} {:.3} {:.3} re f",
+                            left + x * PT,
+                            top - (y + height) * PT,
+                            width * PT,
+                            height * PT
+                        )
+                        .unwrap(),
+                        Mark::Outline {
+                            x,
+                            y,
+                            width,
+                            height,
+                        } => writeln!(
+                            stream,
+                            "q 0.6 G 0.5 w {:.2} {:.2} {:.2} {:.2} re S Q",
+                            left + x * PT,
+                            top - (y + height) * PT,
+                            width * PT,
+                            height * PT
+                        )
+                        .unwrap(),
+                    }
+                }
+                stream.push_str("Q\n");
+            }
+            stream
+        })
+        .collect::<Vec<_>>();
+
+    // Objects 1 and 2 are the catalog and page tree, then the fonts, then a page and its
+    // contents for each sheet
+    let first_page = 3 + PDF_FONTS.len();
+    let page_ids = (0..contents.len())
+        .map(|i| format!("{} 0 R", first_page + i * 2))
+        .collect::<Vec<_>>();
+    let fonts = PDF_FONTS
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| format!("/{name} {} 0 R", 3 + i))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} /MediaBox [0 0 {:.2} {:.2}] >>",
+            page_ids.join(" "),
+            contents.len(),
+            sheet.width * PT,
+            page_height
+        ),
+    ];
+    objects.extend(PDF_FONTS.iter().map(|(_, base)| {
+        format!("<< /Type /Font /Subtype /Type1 /BaseFont /{base} /Encoding /WinAnsiEncoding >>")
+    }));
+    for (i, stream) in contents.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /Resources << /Font << {fonts} >> >> /Contents {} 0 R >>",
+            first_page + i * 2 + 1
+        ));
+        // The line break before `endstream` isn't part of the stream
+        let stream = stream.trim_end_matches('\n');
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{stream}\nendstream",
+            stream.len()
+        ));
+    }
+
+    let mut pdf = "%PDF-1.4\n".to_string();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        writeln!(pdf, "{} 0 obj\n{object}\nendobj", i + 1).unwrap();
+    }
+    let xref = pdf.len();
+    writeln!(pdf, "xref\n0 {}\n0000000000 65535 f ", objects.len() + 1).unwrap();
+    for offset in offsets {
+        writeln!(pdf, "{offset:010} 00000 n ").unwrap();
+    }
+    write!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    )
+    .unwrap();
+    pdf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The barcode as a string of 1s for bars and 0s for spaces, without the quiet zones
+    fn modules(text: &str) -> String {
+        let (bars, width) = code128(text).unwrap();
+        let mut modules = vec![b'0'; width];
+        for (start, bar_width) in bars {
+            modules[start..start + bar_width].fill(b'1');
+        }
+        assert!(modules[..QUIET_ZONE].iter().all(|&m| m == b'0'));
+        assert!(modules[width - QUIET_ZONE..].iter().all(|&m| m == b'0'));
+        String::from_utf8(modules[QUIET_ZONE..width - QUIET_ZONE].to_vec()).unwrap()
+    }
+
+    const START_B: &str = "11010010000";
+    const STOP: &str = "1100011101011";
+
+    #[test]
+    fn code128_matches_reference_patterns() {
+        // "A" is value 33, so the checksum is (104 + 33) % 103 = 34
+        assert_eq!(
+            modules("A"),
+            [START_B, "10100011000", "10001011000", STOP].concat()
+        );
+        // Space is value 0 and "~" is 94, for a checksum of (104 + 0 + 94 * 2) % 103 = 86
+        assert_eq!(
+            modules(" ~"),
+            [START_B, "11011001100", "10001011110", "11110100100", STOP].concat()
+        );
+    }
+
+    #[test]
+    fn code128_weights_the_checksum_by_position() {
+        // 104 + 48 + 42 * 2 + 42 * 3 + 17 * 4 + 18 * 5 + 19 * 6 + 35 * 7 = 879, and 879 % 103 = 55
+        let modules = modules("PJJ123C");
+        assert_eq!(modules.len(), 11 * 9 + 13);
+        assert!(modules.starts_with(START_B));
+        assert!(modules.ends_with(STOP));
+        let checksum = &modules[11 * 8..11 * 9];
+        assert_eq!(checksum, "11101000110");
+    }
+
+    #[test]
+    fn code128_rejects_characters_outside_code_set_b() {
+        assert!(code128("AB\tC").is_none());
+        assert!(code128("Ä").is_none());
+    }
+
+    #[test]
+    fn product_id_adds_a_check_digit() {
+        assert_eq!(product_id(640, 35), "640-0000356");
+        assert_eq!(product_id(11, 123456), "011-1234560");
+        assert_eq!(product_id(999, 999999), "999-9999992");
+        assert_eq!(product_id(0, 0), "000-0000000");
+    }
+
+    fn pdf(count: usize) -> String {
+        let label = Label {
+            key: "FCKGW-RHQQ2-YXRKT-8TG6W-2B7Q8".to_string(),
+            product: "Windows XP Pro VLK (Retail)".to_string(),
+            channel: "640".to_string(),
+            product_id: Some(product_id(640, 35)),
+            machine: Some("LAB-01".to_string()),
+        };
+        let options = SheetOptions {
+            template: LabelTemplate::AveryL7160,
+            skip: 2,
+            outlines: true,
+        };
+        to_pdf(options, &vec![label; count])
+    }
+
+    #[test]
+    fn pdf_xref_points_at_objects() {
+        // Spills over onto a second sheet
+        let pdf = pdf(20);
+        let xref = pdf.rfind("\nxref\n").unwrap() + 1;
+        let startxref = pdf.split("startxref\n").nth(1).unwrap();
+        assert_eq!(startxref.lines().next().unwrap(), xref.to_string());
+
+        let mut lines = pdf[xref..].lines().skip(1);
+        let count: usize = lines.next().unwrap()["0 ".len()..].parse().unwrap();
+        // Catalog, page tree, three fonts, then a page and its contents per sheet
+        assert_eq!(count, 1 + 5 + 2 * 2);
+        assert_eq!(lines.next().unwrap(), "0000000000 65535 f ");
+        for id in 1..count {
+            let entry = lines.next().unwrap();
+            assert!(entry.ends_with(" 00000 n "), "{entry}");
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(
+                pdf[offset..].starts_with(&format!("{id} 0 obj\n")),
+                "object {id} isn't at {offset}"
+            );
+        }
+    }
+
+    #[test]
+    fn pdf_stream_lengths_exclude_the_line_break() {
+        let pdf = pdf(3);
+        let mut streams = 0;
+        for (start, _) in pdf.match_indices("stream\n") {
+            if pdf[..start].ends_with("end") {
+                continue;
+            }
+            let dictionary = &pdf[..start];
+            let length = &dictionary[dictionary.rfind("/Length ").unwrap() + "/Length ".len()..];
+            let length: usize = length[..length.find(' ').unwrap()].parse().unwrap();
+            let data = start + "stream\n".len();
+            assert_eq!(&pdf[data + length..data + length + 11], "\nendstream\n");
+            assert!(!pdf[..data + length].ends_with('\n'));
+            streams += 1;
+        }
+        assert_eq!(streams, 1);
+    }
+}
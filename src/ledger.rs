@@ -0,0 +1,359 @@
+use std::collections::HashSet;
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+use crate::{crypto, storage};
+
+pub const STORAGE_KEY: &str = "mskey-web.ledger";
+
+/// The highest sequence a BINK1998 key can hold
+pub const MAX_SEQUENCE: u32 = 999999;
+
+/// A sequence that's been handed out for a BINK and channel
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub bink: String,
+    pub channel: u32,
+    pub sequence: u32,
+    #[serde(default)]
+    pub product: String,
+    /// Milliseconds since the Unix epoch
+    #[serde(default)]
+    pub issued: u64,
+}
+
+impl LedgerEntry {
+    /// The time it was issued in the user's locale
+    pub fn issued_at(&self) -> String {
+        js_sys::Date::new(&JsValue::from_f64(self.issued as f64))
+            .to_locale_string("default", &JsValue::UNDEFINED)
+            .into()
+    }
+
+    fn same_slot(&self, other: &LedgerEntry) -> bool {
+        self.bink == other.bink && self.channel == other.channel && self.sequence == other.sequence
+    }
+}
+
+/// A record of issued BINK1998 sequences, so that no two keys for a BINK and channel get the same
+/// one. BINK2002 keys don't have a sequence, so they aren't tracked.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Ledger {
+    entries: Vec<LedgerEntry>,
+}
+
+impl Ledger {
+    pub fn load() -> Self {
+        storage::load(STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save(STORAGE_KEY, self);
+    }
+
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    pub fn find(&self, bink_id: u8, channel: u32, sequence: u32) -> Option<&LedgerEntry> {
+        let bink = format!("{bink_id:02X}");
+        self.entries.iter().find(|entry| {
+            entry.bink == bink && entry.channel == channel && entry.sequence == sequence
+        })
+    }
+
+    fn issued(&self, bink_id: u8, channel: u32) -> impl Iterator<Item = u32> + '_ {
+        let bink = format!("{bink_id:02X}");
+        self.entries
+            .iter()
+            .filter(move |entry| entry.bink == bink && entry.channel == channel)
+            .map(|entry| entry.sequence)
+    }
+
+    /// The sequence after the highest one issued, or the lowest free one if that's run out
+    pub fn next_free(&self, bink_id: u8, channel: u32) -> Option<u32> {
+        let mut issued = self.issued(bink_id, channel).collect::<Vec<_>>();
+        issued.sort_unstable();
+        issued.dedup();
+        match issued.last() {
+            None => Some(0),
+            Some(&highest) if highest < MAX_SEQUENCE => Some(highest + 1),
+            // Sorted, the first gap is where a sequence isn't at its own index
+            Some(_) => (0..=MAX_SEQUENCE)
+                .zip(issued)
+                .find(|(sequence, issued)| sequence != issued)
+                .map(|(sequence, _)| sequence),
+        }
+    }
+
+    /// A random sequence that hasn't been issued, given a source of random numbers in [0, 1)
+    pub fn random_free(
+        &self,
+        bink_id: u8,
+        channel: u32,
+        mut random: impl FnMut() -> f64,
+    ) -> Option<u32> {
+        let issued = self.issued(bink_id, channel).collect::<HashSet<_>>();
+        // The ledger would have to be nearly full for this to miss, so don't bother being clever
+        (0..1000)
+            .map(|_| (random() * (MAX_SEQUENCE + 1) as f64) as u32)
+            .find(|sequence| !issued.contains(sequence))
+            .or_else(|| self.next_free(bink_id, channel))
+    }
+
+    /// Records a sequence as issued, returning `false` if it already was
+    pub fn issue(&mut self, entry: LedgerEntry) -> bool {
+        if self.entries.iter().any(|existing| existing.same_slot(&entry)) {
+            return false;
+        }
+        self.entries.push(entry);
+        true
+    }
+
+    /// Records the sequences of freshly generated keys, returning how many were new. BINK2002 keys
+    /// are skipped, as they have no sequence.
+    pub fn issue_keys(
+        &mut self,
+        product: &str,
+        bink_id: u8,
+        keys: &[String],
+        issued: u64,
+    ) -> usize {
+        if bink_id >= 0x40 {
+            return 0;
+        }
+        keys.iter()
+            .filter_map(|key| crypto::bink1998_serial(key))
+            .filter(|&(channel, sequence)| {
+                self.issue(LedgerEntry {
+                    bink: format!("{bink_id:02X}"),
+                    channel,
+                    sequence,
+                    product: product.to_string(),
+                    issued,
+                })
+            })
+            .count()
+    }
+
+    pub fn remove(&mut self, entry: &LedgerEntry) {
+        self.entries.retain(|existing| !existing.same_slot(entry));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Adds the entries from another ledger, keeping the earliest record of each sequence.
+    /// Returns how many sequences were new.
+    pub fn merge(&mut self, other: Ledger) -> usize {
+        let mut added = 0;
+        for entry in other.entries {
+            match self.entries.iter_mut().find(|existing| existing.same_slot(&entry)) {
+                Some(existing) if entry.issued < existing.issued => *existing = entry,
+                Some(_) => {}
+                None => {
+                    self.entries.push(entry);
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+
+    pub fn import(json: &str) -> Result<Ledger> {
+        let mut ledger: Ledger =
+            serde_json::from_str(json).map_err(|err| anyhow!("Not a ledger file: {err}"))?;
+        for entry in &mut ledger.entries {
+            let bink_id = u8::from_str_radix(entry.bink.trim(), 16)
+                .map_err(|_| anyhow!("BINK ID {} is not a hex byte", entry.bink))?;
+            if entry.sequence > MAX_SEQUENCE || entry.channel > 999 {
+                bail!(
+                    "Sequence {} on channel {} is out of range",
+                    entry.sequence,
+                    entry.channel
+                );
+            }
+            entry.bink = format!("{bink_id:02X}");
+        }
+        Ok(ledger)
+    }
+
+    pub fn export(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Records the sequences of keys that have been handed out in the saved ledger, picking up
+/// anything recorded in another tab in the meantime, and returns the updated ledger
+pub fn record_keys(product: &str, bink_id: u8, keys: &[String]) -> Ledger {
+    let mut latest = Ledger::load();
+    if latest.issue_keys(product, bink_id, keys, js_sys::Date::now() as u64) > 0 {
+        latest.save();
+    }
+    latest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::cycle;
+
+    fn entry(bink: &str, channel: u32, sequence: u32, issued: u64) -> LedgerEntry {
+        LedgerEntry {
+            bink: bink.to_string(),
+            channel,
+            sequence,
+            product: "Test 1998".to_string(),
+            issued,
+        }
+    }
+
+    fn ledger(entries: Vec<LedgerEntry>) -> Ledger {
+        Ledger { entries }
+    }
+
+    #[test]
+    fn next_free_follows_the_highest_sequence() {
+        let ledger = ledger(vec![
+            entry("01", 640, 5, 0),
+            entry("01", 640, 2, 0),
+            entry("01", 270, 9, 0),
+            entry("02", 640, 7, 0),
+        ]);
+        assert_eq!(ledger.next_free(0x01, 640), Some(6));
+        assert_eq!(ledger.next_free(0x01, 270), Some(10));
+        assert_eq!(ledger.next_free(0x01, 1), Some(0));
+        assert_eq!(ledger.next_free(0x02, 640), Some(8));
+    }
+
+    #[test]
+    fn next_free_fills_gaps_once_the_top_is_taken() {
+        let ledger = ledger(vec![
+            entry("01", 640, MAX_SEQUENCE, 0),
+            entry("01", 640, 0, 0),
+            entry("01", 640, 1, 0),
+        ]);
+        assert_eq!(ledger.next_free(0x01, 640), Some(2));
+    }
+
+    #[test]
+    fn next_free_finds_the_last_gap_in_a_full_channel() {
+        let ledger = ledger(
+            (0..=MAX_SEQUENCE)
+                .filter(|sequence| *sequence != 765432)
+                .map(|sequence| entry("01", 640, sequence, 0))
+                .collect(),
+        );
+        assert_eq!(ledger.next_free(0x01, 640), Some(765432));
+        assert_eq!(
+            ledger.random_free(0x01, 640, cycle(vec![0.5])),
+            Some(765432)
+        );
+
+        let mut ledger = ledger;
+        assert!(ledger.issue(entry("01", 640, 765432, 0)));
+        assert_eq!(ledger.next_free(0x01, 640), None);
+        assert_eq!(ledger.random_free(0x01, 640, cycle(vec![0.5])), None);
+    }
+
+    #[test]
+    fn random_free_skips_issued_sequences() {
+        let ledger = ledger(vec![entry("01", 640, 500000, 0)]);
+        assert_eq!(
+            ledger.random_free(0x01, 640, cycle(vec![0.5, 0.25])),
+            Some(250000)
+        );
+        // Another channel's sequences don't count
+        assert_eq!(
+            ledger.random_free(0x01, 270, cycle(vec![0.5])),
+            Some(500000)
+        );
+        // Falls back to the next free sequence when every guess is taken
+        assert_eq!(
+            ledger.random_free(0x01, 640, cycle(vec![0.5])),
+            Some(500001)
+        );
+    }
+
+    #[test]
+    fn issue_refuses_issued_sequences() {
+        let mut ledger = Ledger::default();
+        assert!(ledger.issue(entry("01", 640, 1, 10)));
+        assert!(!ledger.issue(entry("01", 640, 1, 20)));
+        assert!(ledger.issue(entry("01", 640, 2, 20)));
+        assert_eq!(ledger.entries().len(), 2);
+    }
+
+    #[test]
+    fn issue_keys_reads_sequences_from_keys() {
+        let mut ledger = Ledger::default();
+        // A BINK1998 key for channel 640 and sequence 123456, and one that isn't a key
+        let keys = [
+            "FHM34-BV66P-RR7WB-JPWD4-VFMHP".to_string(),
+            "not a key".to_string(),
+        ];
+        assert_eq!(ledger.issue_keys("Test 1998", 0x01, &keys, 10), 1);
+        assert_eq!(ledger.entries(), [entry("01", 640, 123456, 10)]);
+        assert_eq!(ledger.issue_keys("Test 1998", 0x01, &keys, 20), 0);
+        // BINK2002 keys have no sequence
+        assert_eq!(ledger.issue_keys("Test 2002", 0x41, &keys, 20), 0);
+        assert_eq!(ledger.entries().len(), 1);
+    }
+
+    #[test]
+    fn merge_keeps_the_earliest_record() {
+        let mut ours = ledger(vec![entry("01", 640, 1, 20), entry("01", 640, 2, 10)]);
+        let theirs = ledger(vec![
+            entry("01", 640, 1, 10),
+            entry("01", 640, 2, 30),
+            entry("01", 640, 3, 30),
+        ]);
+        assert_eq!(ours.merge(theirs), 1);
+        assert_eq!(
+            ours.entries(),
+            [
+                entry("01", 640, 1, 10),
+                entry("01", 640, 2, 10),
+                entry("01", 640, 3, 30),
+            ]
+        );
+    }
+
+    #[test]
+    fn import_normalizes_bink_ids() {
+        let imported = Ledger::import(
+            r#"[{"bink": " 2e", "channel": 640, "sequence": 1}, {"bink": "1", "channel": 0, "sequence": 0}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            imported
+                .entries()
+                .iter()
+                .map(|entry| entry.bink.as_str())
+                .collect::<Vec<_>>(),
+            ["2E", "01"]
+        );
+        assert_eq!(imported.entries()[0].product, "");
+        assert_eq!(
+            Ledger::import(&imported.export().unwrap()).unwrap(),
+            imported
+        );
+    }
+
+    #[test]
+    fn import_rejects_bad_ledgers() {
+        for json in [
+            "not json",
+            r#"{"bink": "01"}"#,
+            r#"[{"bink": "XY", "channel": 640, "sequence": 1}]"#,
+            r#"[{"bink": "01", "channel": 1000, "sequence": 1}]"#,
+            r#"[{"bink": "01", "channel": 640, "sequence": 1000000}]"#,
+        ] {
+            assert!(Ledger::import(json).is_err(), "{json}");
+        }
+    }
+}
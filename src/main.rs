@@ -1,15 +1,39 @@
 use std::rc::Rc;
 
 use leptos::*;
-use wasm_bindgen::prelude::*;
-use web_sys::{Url, UrlSearchParams};
+use leptos_router::*;
 
-use crate::pages::{activate::Activate, generate::Generate, validate::Validate};
+use crate::{
+    components::{button::Button, error::Error},
+    pages::{
+        activate::Activate, custom::Custom, dataset::Dataset, generate::Generate,
+        history::History, home::Home, labels::Labels, ledger::LedgerPage, scan::Scan,
+        settings::Settings, validate::Validate,
+    },
+    routes::{NotFound, Tab},
+};
 
+mod answer_files;
 mod components;
 mod crypto;
+mod detect;
+mod digital_product_id;
+mod files;
+mod history;
 mod icons;
+mod jobs;
+mod labels;
+mod ledger;
 mod pages;
+mod presets;
+mod pwa;
+mod routes;
+mod scanner;
+mod settings;
+mod storage;
+#[cfg(test)]
+mod test_util;
+mod worker;
 
 fn main() {
     console_error_panic_hook::set_once();
@@ -20,120 +44,169 @@ fn main() {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Tab {
-    Generate,
-    Validate,
-    Activate,
-}
+#[component]
+fn App() -> impl IntoView {
+    // Initialize the KeyGen struct into an Rc, which can be cheaply passed around to components.
+    let keygen = Rc::new(crypto::KeyTool::new());
 
-impl Tab {
-    pub fn class(tab: Tab, selected: Tab) -> String {
-        let class = if tab == selected {
-            "inline-block w-full p-3 text-slate-900 bg-slate-200 active focus:outline-none dark:bg-slate-700 dark:text-white"
-        } else {
-            "inline-block w-full p-3 bg-slate-50 hover:text-slate-700 hover:bg-slate-100 focus:outline-none dark:hover:text-white dark:bg-slate-900 dark:hover:bg-slate-700"
-        };
-        match tab {
-            Tab::Generate => format!("{} rounded-t-lg sm:rounded-none sm:rounded-s-lg", class),
-            Tab::Validate => class.to_string(),
-            Tab::Activate => format!("{} rounded-b-lg sm:rounded-none sm:rounded-e-lg", class),
-        }
-    }
-}
+    // Validate-only builds have no private keys to generate with
+    let can_generate = keygen.can_generate_any();
 
-impl std::fmt::Display for Tab {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Tab::Generate => write!(f, "generate"),
-            Tab::Validate => write!(f, "validate"),
-            Tab::Activate => write!(f, "activate"),
-        }
-    }
-}
+    settings::provide_settings();
+    let update_ready = pwa::register();
 
-impl From<UrlSearchParams> for Tab {
-    fn from(search: UrlSearchParams) -> Self {
-        if search.get("activate").is_some() {
-            Tab::Activate
-        } else if search.get("validate").is_some() {
-            Tab::Validate
-        } else {
-            Tab::Generate
-        }
+    // Nearly every tab needs the key workers, so it's said up front if they can't be started
+    let worker_error = worker::pool().err();
+
+    let keygen_generate_clone = keygen.clone();
+    let keygen_validate_clone = keygen.clone();
+    let keygen_validate_key_clone = keygen.clone();
+    let keygen_dataset_clone = keygen.clone();
+    view! {
+        <Router>
+            <Show when=move || update_ready.get()>
+                <div class="max-w-screen-md mx-auto sm:mt-8 px-8 py-3 flex flex-row items-center gap-4 text-slate-100 bg-indigo-600 dark:bg-indigo-700 sm:rounded">
+                    <span class="flex-1">"A new version of the app has been downloaded."</span>
+                    <Button
+                        class="text-slate-900 bg-slate-100 hover:bg-slate-200"
+                        on_click=|_| pwa::apply_update()
+                    >
+                        "Update"
+                    </Button>
+                </div>
+            </Show>
+            <div class="max-w-screen-md mx-auto sm:my-8 px-8 pt-6 pb-8 bg-slate-50 dark:bg-slate-800 sm:shadow-xl rounded">
+                <TabBar can_generate=can_generate />
+                {worker_error
+                    .map(|err| {
+                        view! {
+                            <div class="mb-6">
+                                <Error>{err}</Error>
+                            </div>
+                        }
+                    })}
+                <div class="text-slate-800 dark:text-slate-200">
+                    <Routes>
+                        <Route path="/" view=move || view! { <Home can_generate=can_generate /> } />
+                        <Route
+                            path="/generate"
+                            view=move || {
+                                if can_generate {
+                                    view! { <Generate keygen=keygen_generate_clone.clone() /> }
+                                        .into_view()
+                                } else {
+                                    view! {
+                                        <Redirect path=Tab::Validate.path() options=routes::replace() />
+                                    }
+                                        .into_view()
+                                }
+                            }
+                        />
+                        <Route path="/validate" view=move || view! { <Validate keygen=keygen_validate_clone.clone() /> } />
+                        <Route path="/validate/:key" view=move || view! { <Validate keygen=keygen_validate_key_clone.clone() /> } />
+                        <Route path="/activate" view=Activate />
+                        <Route path="/custom" view=Custom />
+                        <Route path="/dataset" view=move || view! { <Dataset keygen=keygen_dataset_clone.clone() /> } />
+                        <Route path="/history" view=History />
+                        <Route path="/labels" view=Labels />
+                        <Route path="/ledger" view=LedgerPage />
+                        <Route path="/scan" view=Scan />
+                        <Route path="/settings" view=Settings />
+                        <Route path="/*any" view=NotFound />
+                    </Routes>
+                </div>
+            </div>
+            <div class="max-w-screen-md mx-auto mb-8 px-8 flex flex-row justify-end gap-4 text-sm text-slate-500 dark:text-slate-400">
+                <A href="/history" class="hover:underline">
+                    "History"
+                </A>
+                <A href="/labels" class="hover:underline">
+                    "Labels"
+                </A>
+                <A href="/ledger" class="hover:underline">
+                    "Ledger"
+                </A>
+                <A href="/scan" class="hover:underline">
+                    "Scan"
+                </A>
+                <A href="/settings" class="hover:underline">
+                    "Settings"
+                </A>
+            </div>
+        </Router>
     }
 }
 
 #[component]
-fn App() -> impl IntoView {
-    // Initialize the KeyGen struct into an Rc, which can be cheaply passed around to components.
-    let keygen = Rc::new(crypto::KeyTool::new());
-
-    let search = web_sys::window().unwrap().location().search().unwrap();
-    let search = UrlSearchParams::new_with_str(&search).unwrap();
-    let tab: Tab = search.into();
-
-    let (selected_tab, set_selected_tab) = create_signal(tab);
+fn TabBar(can_generate: bool) -> impl IntoView {
+    let location = use_location();
+    let selected_tab = create_memo(move |_| Tab::from_path(&location.pathname.get()));
 
+    // Switching tabs adds a history entry, so the back button returns to the previous tab
     let select_tab = move |tab: Tab| {
-        if selected_tab.get() == tab {
+        if selected_tab.get() == Some(tab) {
             return;
         }
-        let url = web_sys::window().unwrap().location().href().unwrap();
-        let url = Url::new(&url).unwrap();
-        url.set_search(&format!("?{tab}"));
-        let url = url.href();
-        let _ = web_sys::window()
-            .unwrap()
-            .history()
-            .unwrap()
-            .replace_state_with_url(&JsValue::NULL, "", Some(&url));
-        set_selected_tab.set(tab);
+        use_navigate()(&tab.path(), Default::default());
     };
 
     view! {
-        <div class="max-w-screen-md mx-auto sm:my-8 px-8 pt-6 pb-8 bg-slate-50 dark:bg-slate-800 sm:shadow-xl rounded">
-            <ul class="max-w-md mx-auto mb-8 font-medium text-center text-slate-500 rounded-lg shadow sm:flex dark:divide-slate-700 dark:text-slate-400">
-                <li class="w-full">
-                    <button
-                        on:click=move |_| {
-                            select_tab(Tab::Generate);
-                        }
-                        class=move || Tab::class(Tab::Generate, selected_tab.get())
-                    >
-                        "Generate"
-                    </button>
-                </li>
-                <li class="w-full">
-                    <button
-                        on:click=move |_| {
-                            select_tab(Tab::Validate);
-                        }
-                        class=move || Tab::class(Tab::Validate, selected_tab.get())
-                    >
-                        "Validate"
-                    </button>
-                </li>
-                <li class="w-full">
-                    <button
-                        on:click=move |_| {
-                            select_tab(Tab::Activate);
-                        }
-                        class=move || Tab::class(Tab::Activate, selected_tab.get())
-                    >
-                        "Activate"
-                    </button>
-                </li>
-            </ul>
-            <div class="text-slate-800 dark:text-slate-200">
-                {move || {
-                    match selected_tab.get() {
-                        Tab::Generate => view! { <Generate keygen=keygen.clone() /> },
-                        Tab::Validate => view! { <Validate /> },
-                        Tab::Activate => view! { <Activate /> },
+        <ul class="max-w-xl mx-auto mb-8 font-medium text-center text-slate-500 rounded-lg overflow-hidden shadow sm:flex dark:divide-slate-700 dark:text-slate-400">
+            {can_generate
+                .then(|| {
+                    view! {
+                        <li class="w-full">
+                            <button
+                                on:click=move |_| {
+                                    select_tab(Tab::Generate);
+                                }
+                                class=move || Tab::class(Tab::Generate, selected_tab.get())
+                            >
+                                "Generate"
+                            </button>
+                        </li>
                     }
-                }}
-            </div>
-        </div>
+                })}
+            <li class="w-full">
+                <button
+                    on:click=move |_| {
+                        select_tab(Tab::Validate);
+                    }
+                    class=move || Tab::class(Tab::Validate, selected_tab.get())
+                >
+                    "Validate"
+                </button>
+            </li>
+            <li class="w-full">
+                <button
+                    on:click=move |_| {
+                        select_tab(Tab::Activate);
+                    }
+                    class=move || Tab::class(Tab::Activate, selected_tab.get())
+                >
+                    "Activate"
+                </button>
+            </li>
+            <li class="w-full">
+                <button
+                    on:click=move |_| {
+                        select_tab(Tab::Custom);
+                    }
+                    class=move || Tab::class(Tab::Custom, selected_tab.get())
+                >
+                    "Custom"
+                </button>
+            </li>
+            <li class="w-full">
+                <button
+                    on:click=move |_| {
+                        select_tab(Tab::Dataset);
+                    }
+                    class=move || Tab::class(Tab::Dataset, selected_tab.get())
+                >
+                    "Dataset"
+                </button>
+            </li>
+        </ul>
     }
 }
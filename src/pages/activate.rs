@@ -4,26 +4,57 @@ use crate::{
     components::{
         button::Button,
         fields::{Label, TextField},
+        qr_code::QrCode,
     },
-    crypto::KeyTool,
+    history::{self, HistoryItem},
     icons::CopyIcon,
+    routes::{self, Tab},
+    storage, worker,
 };
 
+pub const STORAGE_KEY: &str = "mskey-web.activate";
+
 #[component]
 pub fn Activate() -> impl IntoView {
     use wasm_bindgen_futures::JsFuture;
 
-    let (installation_id, set_installation_id) = create_signal("".to_string());
+    let (installation_id, set_installation_id) = create_signal(
+        routes::query_param("iid")
+            .or_else(|| storage::load(STORAGE_KEY))
+            .unwrap_or_default(),
+    );
+
+    // The installation ID restored from the link or the last visit was recorded when it was
+    // first entered
+    let entered = store_value(false);
 
     let update_installation_id = move |ev| {
-        set_installation_id.set(event_target_value(&ev));
+        let installation_id = event_target_value(&ev);
+        storage::save(STORAGE_KEY, &installation_id);
+        entered.set_value(true);
+        set_installation_id.set(installation_id);
     };
 
-    let confirmation_id = create_memo(move |_| {
-        let installation_id = installation_id.get();
-        KeyTool::get_confirmation_id(&installation_id)
-            .unwrap_or_else(|_| "INVALID INSTALLATION ID".to_string())
-    });
+    let confirmation_id = create_local_resource(
+        move || installation_id.get(),
+        move |installation_id| async move {
+            let confirmation_id = worker::confirmation_id(installation_id.clone()).await;
+            let iid = if let Some(confirmation_id) = &confirmation_id {
+                if entered.get_value() {
+                    history::record(HistoryItem::Activated {
+                        installation_id: installation_id.trim().to_string(),
+                        confirmation_id: confirmation_id.clone(),
+                    });
+                }
+                installation_id.trim()
+            } else {
+                ""
+            };
+            // The page may have been left while the worker was busy
+            routes::replace_url_on(Tab::Activate, "/activate", &[("iid", iid)]);
+            confirmation_id
+        },
+    );
 
     let copy_confirmation_id_action = create_action(|input: &String| {
         let input = input.clone();
@@ -52,12 +83,19 @@ pub fn Activate() -> impl IntoView {
         </div>
         <Label>"Confirmation ID"</Label>
         <div class="overflow-scroll sm:overflow-clip mb-8 mx-auto font-mono text-center text-2xl text-slate-800 rounded-lg p-6 bg-slate-200 dark:bg-slate-700 dark:text-slate-200">
-                {confirmation_id}
+            {move || {
+                confirmation_id
+                    .get()
+                    .map(|confirmation_id| {
+                        confirmation_id.unwrap_or_else(|| "INVALID INSTALLATION ID".to_string())
+                    })
+            }}
         </div>
         <Button
             class="text-slate-100 bg-emerald-500 hover:bg-emerald-600 dark:bg-emerald-600 dark:hover:bg-emerald-700"
             on_click=move |_| {
-                copy_confirmation_id_action.dispatch(confirmation_id.get());
+                copy_confirmation_id_action
+                    .dispatch(confirmation_id.get().flatten().unwrap_or_default());
             }
         >
             <span class="mr-2">
@@ -65,5 +103,8 @@ pub fn Activate() -> impl IntoView {
             </span>
             <span>"Copy"</span>
         </Button>
+        <div class="mt-4">
+            <QrCode value=Signal::derive(move || confirmation_id.get().flatten().unwrap_or_default()) />
+        </div>
     }
 }
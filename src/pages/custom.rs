@@ -0,0 +1,356 @@
+use leptos::*;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{
+    components::{
+        bink1998_details::Bink1998Details,
+        bink2002_details::Bink2002Details,
+        button::Button,
+        error::Error,
+        fields::{CheckBox, Label, NumberField, SelectField, TextField},
+    },
+    crypto::{CustomBink, ProductKey},
+    icons::{CopyIcon, RefreshIcon},
+    settings::use_settings,
+    storage,
+};
+
+const STORAGE_KEY: &str = "mskey-web.custom-binks";
+
+#[component]
+pub fn Custom() -> impl IntoView {
+    let (saved, set_saved) = create_signal(
+        storage::load::<Vec<CustomBink>>(STORAGE_KEY).unwrap_or_default(),
+    );
+
+    let (name, set_name) = create_signal("".to_string());
+    let (bink_id, set_bink_id) = create_signal("".to_string());
+    let (p, set_p) = create_signal("".to_string());
+    let (a, set_a) = create_signal("".to_string());
+    let (b, set_b) = create_signal("".to_string());
+    let (gx, set_gx) = create_signal("".to_string());
+    let (gy, set_gy) = create_signal("".to_string());
+    let (kx, set_kx) = create_signal("".to_string());
+    let (ky, set_ky) = create_signal("".to_string());
+    let (n, set_n) = create_signal("".to_string());
+    let (private, set_private) = create_signal("".to_string());
+
+    let (channel_id, set_channel_id) = create_signal("640".to_string());
+    let (sequence, set_sequence) = create_signal("".to_string());
+    let (upgrade, set_upgrade) = create_signal(false);
+    let (regenerate, set_regenerate) = create_signal(());
+    let (product_key, set_product_key) = create_signal("".to_string());
+
+    let custom_bink = create_memo(move |_| CustomBink {
+        name: name.get(),
+        bink_id: bink_id.get(),
+        p: p.get(),
+        a: a.get(),
+        b: b.get(),
+        gx: gx.get(),
+        gy: gy.get(),
+        kx: kx.get(),
+        ky: ky.get(),
+        n: n.get(),
+        private: private.get(),
+    });
+
+    let load_bink = move |bink: CustomBink| {
+        set_name.set(bink.name);
+        set_bink_id.set(bink.bink_id);
+        set_p.set(bink.p);
+        set_a.set(bink.a);
+        set_b.set(bink.b);
+        set_gx.set(bink.gx);
+        set_gy.set(bink.gy);
+        set_kx.set(bink.kx);
+        set_ky.set(bink.ky);
+        set_n.set(bink.n);
+        set_private.set(bink.private);
+    };
+
+    let update_saved = move |ev| {
+        let name = event_target_value(&ev);
+        let bink = saved
+            .get()
+            .into_iter()
+            .find(|bink| bink.name == name)
+            .unwrap_or_default();
+        load_bink(bink);
+    };
+
+    let save_bink = move |_| {
+        let bink = custom_bink.get();
+        if bink.key_tool().is_err() {
+            return;
+        }
+        set_saved.update(|saved| {
+            saved.retain(|saved| saved.name != bink.name);
+            saved.push(bink);
+            saved.sort_by(|a, b| a.name.cmp(&b.name));
+            storage::save(STORAGE_KEY, saved);
+        });
+    };
+
+    let delete_bink = move |_| {
+        let name = name.get();
+        set_saved.update(|saved| {
+            saved.retain(|saved| saved.name != name);
+            storage::save(STORAGE_KEY, saved);
+        });
+        load_bink(CustomBink::default());
+    };
+
+    let key = create_memo(move |_| {
+        regenerate.get();
+        let bink = custom_bink.get();
+        let Ok(key_tool) = bink.key_tool() else {
+            return "".to_string();
+        };
+        let bink_id = u8::from_str_radix(bink.bink_id.trim(), 16).unwrap();
+        key_tool
+            .gen_key(bink_id, &channel_id.get(), &sequence.get(), "", upgrade.get())
+            .unwrap_or_else(|_| "".to_string())
+    });
+    let settings = use_settings();
+    let formatted_key = move || settings.get().format_key(&key.get());
+
+    let validation = move || {
+        let product_key = product_key.get();
+        if product_key.is_empty() {
+            return Ok(ProductKey::Empty);
+        }
+        let key_tool = custom_bink.get().key_tool()?;
+        key_tool.validate_key(&product_key)
+    };
+
+    let copy_key_action = create_action(|input: &String| {
+        let input = input.clone();
+        async move {
+            JsFuture::from(
+                web_sys::window()
+                    .unwrap()
+                    .navigator()
+                    .clipboard()
+                    .write_text(&input),
+            )
+            .await
+            .err()
+            .map(|_| "Couldn't copy the key to the clipboard".to_string())
+        }
+    });
+
+    let params = [
+        ("Name", "customname", name, set_name),
+        ("BINK ID (hex)", "custombink", bink_id, set_bink_id),
+        ("p", "customp", p, set_p),
+        ("a", "customa", a, set_a),
+        ("b", "customb", b, set_b),
+        ("G.x", "customgx", gx, set_gx),
+        ("G.y", "customgy", gy, set_gy),
+        ("Public Key x", "customkx", kx, set_kx),
+        ("Public Key y", "customky", ky, set_ky),
+        ("n", "customn", n, set_n),
+        ("Private Key (optional)", "customprivate", private, set_private),
+    ]
+    .into_iter()
+    .map(|(label, id, value, set_value)| {
+        view! {
+            <div class="mb-4">
+                <TextField
+                    label=label
+                    id=id
+                    on_change=|_| ()
+                    on_input=move |ev| set_value.set(event_target_value(&ev))
+                    value=value
+                />
+            </div>
+        }
+    })
+    .collect::<Vec<_>>();
+
+    view! {
+        <div class="mb-6">
+            <SelectField label="Saved BINKs" id="customsaved" on_input=update_saved>
+                <option value="" selected=move || saved.get().iter().all(|b| b.name != name.get())>
+                    "New BINK"
+                </option>
+                {move || {
+                    saved
+                        .get()
+                        .into_iter()
+                        .map(|bink| {
+                            let selected = bink.name == name.get();
+                            view! {
+                                <option value=bink.name.clone() selected=selected>
+                                    {bink.name}
+                                </option>
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                }}
+            </SelectField>
+        </div>
+        <p class="mb-4">"Numbers may be entered in decimal or as hex with a 0x prefix."</p>
+        {params}
+        {move || {
+            custom_bink
+                .get()
+                .key_tool()
+                .err()
+                .map(|err| {
+                    view! {
+                        <div class="mb-6">
+                            <Error>{err.to_string()}</Error>
+                        </div>
+                    }
+                })
+        }}
+        <div class="flex flex-row gap-4 mb-8">
+            <Button
+                class="text-slate-100 bg-indigo-500 hover:bg-indigo-600 dark:bg-indigo-600 dark:hover:bg-indigo-700"
+                on_click=save_bink
+            >
+                "Save"
+            </Button>
+            <Button
+                class="text-slate-100 bg-rose-600 hover:bg-rose-700 dark:bg-rose-700 dark:hover:bg-rose-800"
+                on_click=delete_bink
+            >
+                "Delete"
+            </Button>
+        </div>
+        <Label>"Generate"</Label>
+        <div class="flex flex-col sm:flex-row gap-4 mb-6">
+            <div class="flex-1">
+                <NumberField
+                    label="Channel ID"
+                    id="customchannel"
+                    min=0
+                    max=999
+                    value=channel_id
+                    on_input=move |ev| set_channel_id.set(event_target_value(&ev))
+                    placeholder="3 digits"
+                />
+            </div>
+            <div class="flex-1">
+                <NumberField
+                    label="Sequence"
+                    id="customsequence"
+                    min=0
+                    max=999999
+                    value=sequence
+                    on_input=move |ev| set_sequence.set(event_target_value(&ev))
+                    placeholder="Random"
+                />
+            </div>
+            <div class="flex-2">
+                <CheckBox
+                    label="Upgrade"
+                    id="customupgrade"
+                    checked=upgrade
+                    on_change=move |ev| set_upgrade.set(event_target_checked(&ev))
+                />
+            </div>
+        </div>
+        <div class="mb-8 mx-auto font-mono text-center text-2xl text-slate-800 rounded-lg p-6 bg-slate-200 dark:bg-slate-700 dark:text-slate-200">
+            {move || {
+                if custom_bink.with(|bink| bink.private.trim().is_empty()) {
+                    "Enter a private key to generate keys".to_string()
+                } else {
+                    formatted_key()
+                }
+            }}
+        </div>
+        <div class="flex flex-row gap-4 mb-8">
+            <Button
+                class="text-slate-100 bg-emerald-500 hover:bg-emerald-600 dark:bg-emerald-600 dark:hover:bg-emerald-700"
+                on_click=move |_| {
+                    copy_key_action.dispatch(formatted_key());
+                }
+            >
+                <span class="mr-2">
+                    <CopyIcon />
+                </span>
+                <span>"Copy"</span>
+            </Button>
+            <Button
+                class="text-slate-100 bg-indigo-500 hover:bg-indigo-600 dark:bg-indigo-600 dark:hover:bg-indigo-700"
+                on_click=move |_| {
+                    set_regenerate.set(());
+                }
+            >
+                <span class="mr-2">
+                    <RefreshIcon />
+                </span>
+                <span>"Regenerate"</span>
+            </Button>
+        </div>
+        {move || {
+            copy_key_action
+                .value()
+                .get()
+                .flatten()
+                .map(|err| {
+                    view! {
+                        <div class="mb-8">
+                            <Error>{err}</Error>
+                        </div>
+                    }
+                })
+        }}
+        <div class="mb-4">
+            <TextField
+                label="Validate Product Key"
+                id="customproductkey"
+                on_change=|_| ()
+                on_input=move |ev| set_product_key.set(event_target_value(&ev))
+                value=product_key
+            />
+        </div>
+        {move || match validation() {
+            Ok(ProductKey::Empty) => view! { <div></div> },
+            // PIDGEN2 keys don't use a BINK, so they're no more valid for this one than any other
+            Ok(
+                ProductKey::Invalid
+                | ProductKey::Pidgen2Cd { .. }
+                | ProductKey::Pidgen2Oem { .. },
+            ) => {
+                view! {
+                    <div class="mt-6">
+                        <Error>"Invalid product key"</Error>
+                    </div>
+                }
+            }
+            Ok(ProductKey::Bink1998 { key, bink_ids, channel_kinds }) => {
+                view! {
+                    <div>
+                        <Bink1998Details
+                            key=key
+                            bink_ids=bink_ids
+                            channel_kinds=channel_kinds
+                        />
+                    </div>
+                }
+            }
+            Ok(ProductKey::Bink2002 { key, bink_ids, channel_kinds }) => {
+                view! {
+                    <div>
+                        <Bink2002Details
+                            key=key
+                            bink_ids=bink_ids
+                            channel_kinds=channel_kinds
+                        />
+                    </div>
+                }
+            }
+            Err(_) => {
+                view! {
+                    <div class="mt-6">
+                        <Error>"Error validating key"</Error>
+                    </div>
+                }
+            }
+        }}
+    }
+}
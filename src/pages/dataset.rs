@@ -0,0 +1,195 @@
+use std::rc::Rc;
+
+use leptos::*;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{
+    components::{
+        button::Button,
+        error::Error,
+        fields::{Label, TextField},
+    },
+    crypto::KeyTool,
+    icons::CopyIcon,
+};
+
+#[component]
+pub fn Dataset(keygen: Rc<KeyTool>) -> impl IntoView {
+    let (search, set_search) = create_signal("".to_string());
+
+    let update_search = move |ev| {
+        set_search.set(event_target_value(&ev));
+    };
+
+    // Fails with the BINK whose JSON couldn't be copied, so the error is shown next to it
+    let copy_json_action = create_action(|(bink_id, json): &(u8, String)| {
+        let bink_id = *bink_id;
+        let json = json.clone();
+        async move {
+            JsFuture::from(
+                web_sys::window()
+                    .unwrap()
+                    .navigator()
+                    .clipboard()
+                    .write_text(&json),
+            )
+            .await
+            .err()
+            .map(|_| (bink_id, "Couldn't copy the JSON to the clipboard".to_string()))
+        }
+    });
+
+    let keygen_products_clone = keygen.clone();
+    let products = move || {
+        let search = search.get();
+        keygen_products_clone
+            .sorted_products()
+            .iter()
+            .filter(|p| {
+                p.matches(&search)
+                    || p
+                        .bink_ids
+                        .iter()
+                        .any(|b| format!("{:02X}", b).eq_ignore_ascii_case(search.trim()))
+            })
+            .enumerate()
+            .map(|(i, p)| {
+                let class = if i % 2 == 0 { "" } else { "bg-slate-100 dark:bg-slate-700" };
+                let bink_ids = p
+                    .bink_ids
+                    .iter()
+                    .map(|b| p.bink_label(*b))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                view! {
+                    <tr class=class>
+                        <td class="px-4 py-2">{p.name.clone()}</td>
+                        <td class="px-4 py-2">{p.family.clone()}</td>
+                        <td class="font-mono px-4 py-2">{bink_ids}</td>
+                    </tr>
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let keygen_binks_clone = keygen.clone();
+    let binks = move || {
+        let search = search.get();
+        let search = search.trim().to_lowercase();
+        keygen_binks_clone
+            .bink_ids()
+            .into_iter()
+            .filter_map(|bink_id| {
+                let bink = keygen_binks_clone.bink(bink_id)?;
+                let products = keygen_binks_clone.bink_products(bink_id);
+                let bink_str = format!("{:02X}", bink_id);
+                if !search.is_empty()
+                    && bink_str.to_lowercase() != search
+                    && !products.iter().any(|p| p.to_lowercase().contains(&search))
+                {
+                    return None;
+                }
+                let family = if bink_id < 0x40 { "BINK1998" } else { "BINK2002" };
+                let private = if bink.private.is_some() {
+                    "private key included"
+                } else {
+                    "validation only"
+                };
+                let json = keygen_binks_clone.bink_json(bink_id).unwrap_or_default();
+                let params = [
+                    ("p", bink.p.to_string()),
+                    ("a", bink.a.to_string()),
+                    ("b", bink.b.to_string()),
+                    ("G.x", bink.g.x.to_string()),
+                    ("G.y", bink.g.y.to_string()),
+                    ("K.x", bink.public.x.to_string()),
+                    ("K.y", bink.public.y.to_string()),
+                    ("n", bink.n.to_string()),
+                ]
+                .into_iter()
+                .enumerate()
+                .map(|(i, (name, value))| {
+                    let class = if i % 2 == 0 { "" } else { "bg-slate-100 dark:bg-slate-700" };
+                    view! {
+                        <tr class=class>
+                            <th class="font-semibold px-4 py-2">{name}</th>
+                            <td class="font-mono break-all px-4 py-2">{value}</td>
+                        </tr>
+                    }
+                })
+                .collect::<Vec<_>>();
+                Some(
+                    view! {
+                        <div class="mb-8">
+                            <Label>{format!("BINK {bink_str}")}</Label>
+                            <p class="mb-2">
+                                {format!("{family}, {} bit curve, {private}", bink.p.bits())}
+                            </p>
+                            <p class="mb-2">
+                                {if products.is_empty() {
+                                    "Not used by any product".to_string()
+                                } else {
+                                    format!("Used by: {}", products.join(", "))
+                                }}
+                            </p>
+                            <table class="w-full text-left mb-4">
+                                <tbody>{params}</tbody>
+                            </table>
+                            <Button
+                                class="text-slate-100 bg-emerald-500 hover:bg-emerald-600 dark:bg-emerald-600 dark:hover:bg-emerald-700"
+                                on_click=move |_| {
+                                    copy_json_action.dispatch((bink_id, json.clone()));
+                                }
+                            >
+                                <span class="mr-2">
+                                    <CopyIcon />
+                                </span>
+                                <span>"Copy as JSON"</span>
+                            </Button>
+                            {move || {
+                                copy_json_action
+                                    .value()
+                                    .get()
+                                    .flatten()
+                                    .filter(|(failed, _)| *failed == bink_id)
+                                    .map(|(_, err)| {
+                                        view! {
+                                            <div class="mt-4">
+                                                <Error>{err}</Error>
+                                            </div>
+                                        }
+                                    })
+                            }}
+                        </div>
+                    },
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <div class="mb-6">
+            <TextField
+                label="Search"
+                id="datasetsearch"
+                on_change=|_| ()
+                on_input=update_search
+                value=search
+            />
+        </div>
+        <div class="mb-8">
+            <Label>"Products"</Label>
+            <table class="w-full text-left">
+                <thead>
+                    <tr>
+                        <th class="font-semibold px-4 py-2">"Name"</th>
+                        <th class="font-semibold px-4 py-2">"Family"</th>
+                        <th class="font-semibold px-4 py-2">"BINK"</th>
+                    </tr>
+                </thead>
+                <tbody>{products}</tbody>
+            </table>
+        </div>
+        {binks}
+    }
+}
@@ -1,24 +1,142 @@
-use std::rc::Rc;
+use std::{rc::Rc, time::Duration};
 
-use leptos::*;
+use leptos::{leptos_dom::helpers::TimeoutHandle, *};
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen_futures::JsFuture;
 
 use crate::{
     components::{
+        answer_files::AnswerFiles,
         button::Button,
-        fields::{CheckBox, NumberField, SelectField},
+        error::Error,
+        fields::{CheckBox, NumberField, SelectField, TextField},
+        loading::Loading,
+        pidgen2_keys::Pidgen2Keys,
+        progress::JobProgress,
+        qr_code::QrCode,
+        warning::Warning,
     },
     crypto::KeyTool,
+    files,
+    history::{self, HistoryItem},
     icons::{CopyIcon, RefreshIcon},
+    jobs::{create_job, Job, JobOutput, JobStatus},
+    ledger::{self, Ledger},
+    pages::{labels, ledger::SequenceLedger},
+    presets::{self, Preset, SequencePolicy},
+    routes,
+    settings::use_settings,
+    storage,
+    worker::{self, GenKeyRequest},
 };
 
+/// How long to wait for input to settle before generating a key
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+const DEFAULT_PRODUCT: &str = "Windows XP Pro VLK";
+const DEFAULT_CHANNEL: &str = "640";
+
+/// The most keys a single bulk job will generate
+const MAX_BULK_COUNT: u32 = 10000;
+
+pub const STORAGE_KEY: &str = "mskey-web.generate";
+
+/// The query parameters that fill in the form. A link with any of them starts from a blank form
+/// rather than the last visit.
+const QUERY_FIELDS: [&str; 5] = ["product", "bink", "channel", "sequence", "upgrade"];
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct FormState {
+    product: String,
+    bink_id: u8,
+    channel_id: String,
+    sequence: String,
+    upgrade: bool,
+}
+
+impl FormState {
+    /// Reads the form from the query string, or from the last visit if there isn't one, falling
+    /// back to the defaults for anything missing or not in the dataset
+    fn load(keygen: &KeyTool) -> Self {
+        let saved = if QUERY_FIELDS
+            .iter()
+            .any(|field| routes::query_param(field).is_some())
+        {
+            None
+        } else {
+            storage::load::<FormState>(STORAGE_KEY)
+        };
+        let saved = saved.as_ref();
+
+        let product = routes::query_param("product")
+            .or(saved.map(|saved| saved.product.clone()))
+            .and_then(|name| {
+                keygen
+                    .sorted_products()
+                    .iter()
+                    .find(|product| product.name == name)
+            })
+            .or_else(|| {
+                keygen
+                    .sorted_products()
+                    .iter()
+                    .find(|product| product.name == DEFAULT_PRODUCT)
+            })
+            .or(keygen.sorted_products().first());
+        let bink_ids = product.map(|product| product.bink_ids.as_slice()).unwrap_or_default();
+        let bink_id = routes::query_param("bink")
+            .and_then(|bink_id| u8::from_str_radix(&bink_id, 16).ok())
+            .or(saved.map(|saved| saved.bink_id))
+            .filter(|bink_id| bink_ids.contains(bink_id))
+            .or_else(|| bink_ids.iter().copied().find(|b| keygen.can_generate(*b)))
+            .or(bink_ids.first().copied())
+            .unwrap_or_default();
+        let channel_id = routes::query_param("channel")
+            .or(saved.map(|saved| saved.channel_id.clone()))
+            .filter(|channel| channel.parse::<u16>().is_ok_and(|channel| channel <= 999))
+            .unwrap_or_else(|| DEFAULT_CHANNEL.to_string());
+        let sequence = routes::query_param("sequence")
+            .or(saved.map(|saved| saved.sequence.clone()))
+            .filter(|sequence| sequence.parse::<u32>().is_ok_and(|sequence| sequence <= 999999))
+            .unwrap_or_default();
+        let upgrade = routes::query_param("upgrade")
+            .map(|upgrade| upgrade == "1")
+            .or(saved.map(|saved| saved.upgrade))
+            .unwrap_or_default();
+
+        Self {
+            product: product
+                .map(|product| product.name.clone())
+                .unwrap_or_default(),
+            bink_id,
+            channel_id,
+            sequence,
+            upgrade,
+        }
+    }
+}
+
+fn generated(product: &str, request: &GenKeyRequest, key: String) -> HistoryItem {
+    HistoryItem::Generated {
+        key,
+        product: product.to_string(),
+        bink: format!("{:02X}", request.bink_id),
+        channel: request.channel_id.clone(),
+        sequence: request.sequence.clone(),
+        upgrade: request.upgrade,
+    }
+}
+
 #[component]
 pub fn Generate(keygen: Rc<KeyTool>) -> impl IntoView {
-    let (product, set_product) = create_signal("Windows XP Pro VLK".to_string());
-    let (bink_id, set_bink_id) = create_signal(0x2E_u8);
-    let (channel_id, set_channel_id) = create_signal("640".to_string());
-    let (sequence, set_sequence) = create_signal("".to_string());
-    let (upgrade, set_upgrade) = create_signal(false);
+    let initial = FormState::load(&keygen);
+    let (product, set_product) = create_signal(initial.product);
+    let (bink_id, set_bink_id) = create_signal(initial.bink_id);
+    let (channel_id, set_channel_id) = create_signal(initial.channel_id);
+    let (sequence, set_sequence) = create_signal(initial.sequence);
+    let (upgrade, set_upgrade) = create_signal(initial.upgrade);
+    let (product_search, set_product_search) = create_signal("".to_string());
 
     let update_upgrade = move |ev| {
         set_upgrade.set(event_target_checked(&ev));
@@ -38,6 +156,10 @@ pub fn Generate(keygen: Rc<KeyTool>) -> impl IntoView {
         set_sequence.set(event_target_value(&ev));
     };
 
+    let update_product_search = move |ev| {
+        set_product_search.set(event_target_value(&ev));
+    };
+
     let keygen_product_clone = keygen.clone();
     let update_product = move |ev| {
         let product_name = event_target_value(&ev);
@@ -47,18 +169,120 @@ pub fn Generate(keygen: Rc<KeyTool>) -> impl IntoView {
             .find(|product| product.name == product_name)
             .unwrap();
         set_product.set(product_name);
-        let bink_id = product.bink_ids.first().unwrap();
+        let bink_id = product
+            .bink_ids
+            .iter()
+            .find(|b| keygen_product_clone.can_generate(**b))
+            .or(product.bink_ids.first())
+            .unwrap();
         set_bink_id.set(*bink_id);
     };
 
-    let keygen_key_clone = keygen.clone();
-    let key = create_memo(move |_| {
-        let bink_id = bink_id.get();
-        keygen_key_clone
-            .gen_key(bink_id, &channel_id.get(), &sequence.get(), upgrade.get())
-            .unwrap_or_else(|_| "".to_string())
+    let keygen_preset_clone = keygen.clone();
+    let update_channel_preset = move |ev| {
+        let Ok(index) = event_target_value(&ev).parse::<usize>() else {
+            return;
+        };
+        if let Some(range) = keygen_preset_clone
+            .channel_presets(&product.get())
+            .get(index)
+        {
+            set_channel_id.set(format!("{:03}", range.min));
+        }
+    };
+
+    // Keep the address bar in step with the form, so the link reproduces it, and remember the
+    // form for the next visit
+    create_effect(move |_| {
+        let state = FormState {
+            product: product.get(),
+            bink_id: bink_id.get(),
+            channel_id: channel_id.get(),
+            sequence: sequence.get(),
+            upgrade: upgrade.get(),
+        };
+        routes::replace_url(
+            "/generate",
+            &[
+                ("product", &state.product),
+                ("bink", &format!("{:02X}", state.bink_id)),
+                ("channel", &state.channel_id),
+                ("sequence", &state.sequence),
+                ("upgrade", if state.upgrade { "1" } else { "" }),
+            ],
+        );
+        storage::save(STORAGE_KEY, &state);
+    });
+
+    // `None` while a key is being generated
+    let (key, set_key) = create_signal(None::<String>);
+    let latest_request = store_value(0_u64);
+    // What the key on show was generated from, until it's been recorded in the history
+    let unrecorded = store_value(None::<(String, GenKeyRequest)>);
+    // Set by the Regenerate button, so the key it asked for is recorded as soon as it arrives
+    let record_next = store_value(false);
+
+    // The issued sequences, shared with the ledger controls so they see keys as they're recorded
+    let sequence_ledger = create_rw_signal(Ledger::load());
+
+    // Keys are generated as the form is filled in, so they're only recorded, in the history and
+    // the sequence ledger, once the user does something with one
+    let record_key = move || {
+        let Some(key) = key.get_untracked().filter(|key| !key.is_empty()) else {
+            return;
+        };
+        if let Some((product, request)) = unrecorded.get_value() {
+            unrecorded.set_value(None);
+            sequence_ledger.set(ledger::record_keys(
+                &product,
+                request.bink_id,
+                std::slice::from_ref(&key),
+            ));
+            history::record(generated(&product, &request, key));
+        }
+    };
+
+    // Wait for the inputs to settle, then generate in the worker. Results from requests that
+    // have since been superseded are dropped.
+    create_effect(move |pending: Option<Option<TimeoutHandle>>| {
+        let request = GenKeyRequest {
+            bink_id: bink_id.get(),
+            channel_id: channel_id.get(),
+            sequence: sequence.get(),
+            auth_info: String::new(),
+            upgrade: upgrade.get(),
+        };
+        if let Some(Some(pending)) = pending {
+            pending.clear();
+        }
+        latest_request.update_value(|id| *id += 1);
+        let id = latest_request.get_value();
+        let product = product.get_untracked();
+        let record = record_next.get_value();
+        record_next.set_value(false);
+        unrecorded.set_value(None);
+        set_key.set(None);
+        set_timeout_with_handle(
+            move || {
+                spawn_local(async move {
+                    let key = worker::gen_key(request.clone()).await.unwrap_or_default();
+                    if latest_request.get_value() == id {
+                        unrecorded.set_value(Some((product, request)));
+                        set_key.set(Some(key));
+                        if record {
+                            record_key();
+                        }
+                    }
+                });
+            },
+            DEBOUNCE,
+        )
+        .ok()
     });
 
+    let settings = use_settings();
+    let formatted_key = move || key.get().map(|key| settings.get().format_key(&key));
+
     let copy_key_action = create_action(|input: &String| {
         let input = input.clone();
         async move {
@@ -74,40 +298,229 @@ pub fn Generate(keygen: Rc<KeyTool>) -> impl IntoView {
         }
     });
 
+    let (bulk_count, set_bulk_count) = create_signal("100".to_string());
+    let bulk_job = create_job();
+
+    let update_bulk_count = move |ev| {
+        set_bulk_count.set(event_target_value(&ev));
+    };
+
+    // What the bulk job was started with, until its keys have been recorded
+    let bulk_request = store_value(None::<(String, GenKeyRequest)>);
+
+    // Records the keys from a bulk job in one go once it stops. Taking the request means each
+    // job's keys are only recorded once, whichever of the effect and a new job gets there first.
+    let record_bulk_job = move || {
+        let Some((product, request)) = bulk_request.get_value() else {
+            return;
+        };
+        bulk_request.set_value(None);
+        let keys: Vec<String> = untrack(|| bulk_job.outputs())
+            .into_iter()
+            .filter_map(|output| match output {
+                JobOutput::Key(key) => Some(key),
+                _ => None,
+            })
+            .collect();
+        sequence_ledger.set(ledger::record_keys(&product, request.bink_id, &keys));
+        history::record_all(
+            keys.into_iter()
+                .map(|key| generated(&product, &request, key))
+                .collect(),
+        );
+    };
+
+    create_effect(move |_| {
+        if matches!(bulk_job.status.get(), JobStatus::Finished | JobStatus::Cancelled) {
+            record_bulk_job();
+        }
+    });
+
+    let start_bulk_job = move |_| {
+        let count = bulk_count
+            .get()
+            .parse::<u32>()
+            .unwrap_or(0)
+            .min(MAX_BULK_COUNT);
+        let request = GenKeyRequest {
+            bink_id: bink_id.get(),
+            channel_id: channel_id.get(),
+            sequence: sequence.get(),
+            auth_info: String::new(),
+            upgrade: upgrade.get(),
+        };
+        // The job that's still running has to be stopped and recorded with its own parameters
+        // before they're replaced
+        bulk_job.cancel();
+        record_bulk_job();
+        bulk_request.set_value(Some((product.get(), request.clone())));
+        bulk_job.start(Job::Generate {
+            request,
+            count: count as usize,
+        });
+    };
+
+    let bulk_key_list = Signal::derive(move || {
+        bulk_job
+            .outputs()
+            .into_iter()
+            .filter_map(|output| match output {
+                JobOutput::Key(key) => Some(key),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let bulk_keys = move || {
+        bulk_key_list
+            .get()
+            .iter()
+            .map(|key| settings.get().format_key(key))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let current_preset = Signal::derive(move || Preset {
+        name: String::new(),
+        product: product.get(),
+        bink: format!("{:02X}", bink_id.get()),
+        channel: channel_id.get(),
+        sequence: SequencePolicy::from_field(&sequence.get()),
+        upgrade: upgrade.get(),
+    });
+    let apply_preset = Callback::new(move |preset: Preset| {
+        // Presets are checked against the dataset when they're saved or imported
+        let Ok(bink_id) = preset.bink_id() else {
+            return;
+        };
+        set_product.set(preset.product);
+        set_bink_id.set(bink_id);
+        set_channel_id.set(preset.channel);
+        set_sequence.set(preset.sequence.to_field());
+        set_upgrade.set(preset.upgrade);
+    });
+
     let keygen_view_clone_1 = keygen.clone();
     let keygen_view_clone_2 = keygen.clone();
+    let keygen_view_clone_3 = keygen.clone();
+    let keygen_view_clone_4 = keygen.clone();
+    let keygen_view_clone_5 = keygen.clone();
     view! {
+        <Presets keygen=keygen.clone() current=current_preset on_apply=apply_preset />
         <div class="mb-4">
-            <SelectField label="Product" id="product" on_input=update_product>
-                {keygen_view_clone_1
-                    .sorted_products()
-                    .iter()
-                    .map(|p| {
-                        view! {
-                            <option value=p.name.clone() selected=p.name == product.get()>
-                                {p.name.clone()}
+            <TextField
+                label="Search Products"
+                id="productsearch"
+                on_change=|_| ()
+                on_input=update_product_search
+                value=product_search
+            />
+        </div>
+        <div class="flex flex-col sm:flex-row gap-4 mb-4">
+            <div class="flex-[2]">
+                <SelectField label="Product" id="product" on_input=update_product>
+                    {move || {
+                        let search = product_search.get();
+                        let selected = product.get();
+                        keygen_view_clone_1
+                            .product_families()
+                            .into_iter()
+                            .filter_map(|(family, products)| {
+                                let options = products
+                                    .into_iter()
+                                    .filter(|p| p.name == selected || p.matches(&search))
+                                    .map(|p| {
+                                        let title = [
+                                            p.edition.clone(),
+                                            p.year.map(|year| year.to_string()),
+                                            (!p.codes.is_empty()).then(|| p.codes.join(", ")),
+                                        ]
+                                        .into_iter()
+                                        .flatten()
+                                        .collect::<Vec<_>>()
+                                        .join(" | ");
+                                        view! {
+                                            <option
+                                                value=p.name.clone()
+                                                title=title
+                                                selected=p.name == selected
+                                            >
+                                                {p.name.clone()}
+                                            </option>
+                                        }
+                                    })
+                                    .collect::<Vec<_>>();
+                                if options.is_empty() {
+                                    None
+                                } else {
+                                    Some(view! { <optgroup label=family.to_string()>{options}</optgroup> })
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    }}
+                </SelectField>
+            </div>
+            <div class="flex-1">
+                <SelectField label="Channel Type" id="channelpreset" on_input=update_channel_preset>
+                    {move || {
+                        let presets = keygen_view_clone_3.channel_presets(&product.get());
+                        let matched = channel_id
+                            .get()
+                            .parse::<u32>()
+                            .ok()
+                            .and_then(|channel| {
+                                presets.iter().position(|range| range.contains(channel))
+                            });
+                        let custom = view! {
+                            <option value="custom" selected=matched.is_none()>
+                                "Custom"
                             </option>
-                        }
-                    })
-                    .collect::<Vec<_>>()}
-            </SelectField>
+                        };
+                        std::iter::once(custom)
+                            .chain(
+                                presets
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(i, range)| {
+                                        view! {
+                                            <option value=i.to_string() selected=matched == Some(i)>
+                                                {range.to_string()}
+                                            </option>
+                                        }
+                                    }),
+                            )
+                            .collect::<Vec<_>>()
+                    }}
+                </SelectField>
+            </div>
         </div>
         <div class="flex flex-col sm:flex-row gap-4 mb-6">
             <div class="flex-1">
                 <SelectField label="BINK ID" id="bink" on_input=update_bink_id>
                     {move || {
-                        keygen_view_clone_2
+                        let product = keygen_view_clone_2
                             .sorted_products()
                             .iter()
                             .find(|p| p.name == product.get())
-                            .unwrap()
+                            .unwrap();
+                        product
                             .bink_ids
                             .iter()
                             .map(|b| {
                                 let b_str = format!("{:02X}", b);
+                                let can_generate = keygen_view_clone_2.can_generate(*b);
+                                let label = if can_generate {
+                                    product.bink_label(*b)
+                                } else {
+                                    format!("{} - no private key", product.bink_label(*b))
+                                };
                                 view! {
-                                    <option value=b_str.clone() selected=*b == bink_id.get()>
-                                        {b_str}
+                                    <option
+                                        value=b_str
+                                        selected=*b == bink_id.get()
+                                        disabled=!can_generate
+                                    >
+                                        {label}
                                     </option>
                                 }
                             })
@@ -141,14 +554,42 @@ pub fn Generate(keygen: Rc<KeyTool>) -> impl IntoView {
                 <CheckBox label="Upgrade" id="upgrade" checked=upgrade on_change=update_upgrade />
             </div>
         </div>
+        {move || {
+            let product = product.get();
+            let channel = channel_id.get().parse::<u32>().ok()?;
+            if keygen_view_clone_4.channel_presets(&product).is_empty()
+                || keygen_view_clone_4.channel_kind(&product, channel).is_some()
+            {
+                return None;
+            }
+            Some(
+                view! {
+                    <div class="mb-6">
+                        <Warning>
+                            {format!("Channel {channel:03} is not a known channel for {product}")}
+                        </Warning>
+                    </div>
+                },
+            )
+        }}
         <div class="mb-8 mx-auto font-mono text-center text-2xl text-slate-800 rounded-lg p-6 bg-slate-200 dark:bg-slate-700 dark:text-slate-200">
-            {key}
+            {move || {
+                if !keygen_view_clone_5.can_generate(bink_id.get()) {
+                    "No private key for this BINK".into_view()
+                } else {
+                    match formatted_key() {
+                        Some(key) => key.into_view(),
+                        None => view! { <Loading /> }.into_view(),
+                    }
+                }
+            }}
         </div>
         <div class="flex flex-row gap-4">
             <Button
                 class="text-slate-100 bg-emerald-500 hover:bg-emerald-600 dark:bg-emerald-600 dark:hover:bg-emerald-700"
                 on_click=move |_| {
-                    copy_key_action.dispatch(key.get());
+                    record_key();
+                    copy_key_action.dispatch(formatted_key().unwrap_or_default());
                 }
             >
                 <span class="mr-2">
@@ -159,6 +600,7 @@ pub fn Generate(keygen: Rc<KeyTool>) -> impl IntoView {
             <Button
                 class="text-slate-100 bg-indigo-500 hover:bg-indigo-600 dark:bg-indigo-600 dark:hover:bg-indigo-700"
                 on_click=move |_| {
+                    record_next.set_value(true);
                     set_bink_id.update(|_| ());
                 }
             >
@@ -168,5 +610,221 @@ pub fn Generate(keygen: Rc<KeyTool>) -> impl IntoView {
                 <span>"Regenerate"</span>
             </Button>
         </div>
+        <div class="mt-4">
+            <QrCode value=Signal::derive(move || formatted_key().unwrap_or_default()) />
+        </div>
+        // Only BINK1998 keys have a sequence to keep track of
+        <Show when=move || { bink_id.get() < 0x40 }>
+            <div class="mt-8">
+                <SequenceLedger
+                    bink_id=bink_id
+                    channel_id=channel_id
+                    sequence=sequence
+                    set_sequence=set_sequence
+                    ledger=sequence_ledger
+                />
+            </div>
+        </Show>
+        <h2 class="mt-10 mb-4 font-bold text-xl">"Bulk Generate"</h2>
+        <div class="flex flex-col sm:flex-row sm:items-end gap-4 mb-4">
+            <div class="flex-1">
+                <NumberField
+                    label="Number of Keys"
+                    id="bulkcount"
+                    min=1
+                    max=MAX_BULK_COUNT
+                    value=bulk_count
+                    on_input=update_bulk_count
+                    placeholder="100"
+                />
+            </div>
+            <div class="flex-1">
+                <Button
+                    class="w-full h-10 py-0 text-slate-100 bg-indigo-500 hover:bg-indigo-600 dark:bg-indigo-600 dark:hover:bg-indigo-700"
+                    on_click=start_bulk_job
+                >
+                    "Generate Keys"
+                </Button>
+            </div>
+        </div>
+        <div class="mb-4">
+            <JobProgress job=bulk_job />
+        </div>
+        <Show when=move || { bulk_job.results.with(|results| !results.is_empty()) }>
+            <textarea
+                class="block w-full h-48 mb-4 px-3 py-2 rounded-lg font-mono bg-slate-100 dark:bg-slate-900"
+                readonly
+                prop:value=bulk_keys
+            ></textarea>
+            <Button
+                class="text-slate-100 bg-emerald-500 hover:bg-emerald-600 dark:bg-emerald-600 dark:hover:bg-emerald-700"
+                on_click=move |_| {
+                    copy_key_action.dispatch(bulk_keys());
+                }
+            >
+                <span class="mr-2">
+                    <CopyIcon />
+                </span>
+                <span>"Copy All"</span>
+            </Button>
+            <Button
+                class="ml-4 bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600"
+                on_click=move |_| {
+                    let mut form = labels::FormState::load();
+                    form.keys = bulk_keys();
+                    form.save();
+                    use_navigate()("/labels", Default::default());
+                }
+            >
+                "Print Labels"
+            </Button>
+        </Show>
+        <h2 class="mt-10 mb-4 font-bold text-xl">"Answer File"</h2>
+        <AnswerFiles
+            key=key
+            bulk_keys=bulk_key_list
+            on_download=Callback::new(move |_| record_key())
+        />
+        <h2 class="mt-10 mb-4 font-bold text-xl">"PIDGEN2 Keys"</h2>
+        <Pidgen2Keys />
+    }
+}
+
+/// Saving, applying and sharing named sets of Generate parameters
+#[component]
+fn Presets(
+    keygen: Rc<KeyTool>,
+    current: Signal<Preset>,
+    on_apply: Callback<Preset>,
+) -> impl IntoView {
+    let (saved, set_saved) = create_signal(
+        storage::load::<Vec<Preset>>(presets::STORAGE_KEY).unwrap_or_default(),
+    );
+    let (name, set_name) = create_signal("".to_string());
+    let (error, set_error) = create_signal(None::<String>);
+
+    let update_saved = move |ev| {
+        let preset_name = event_target_value(&ev);
+        if let Some(preset) = saved.get().into_iter().find(|p| p.name == preset_name) {
+            set_name.set(preset.name.clone());
+            set_error.set(None);
+            on_apply.call(preset);
+        }
+    };
+
+    let keygen_save_clone = keygen.clone();
+    let save_preset = move |_| {
+        let preset = Preset {
+            name: name.get().trim().to_string(),
+            ..current.get()
+        };
+        if let Err(err) = preset.check(&keygen_save_clone) {
+            set_error.set(Some(err.to_string()));
+            return;
+        }
+        set_error.set(None);
+        set_saved.update(|saved| {
+            presets::merge(saved, [preset]);
+            storage::save(presets::STORAGE_KEY, saved);
+        });
+    };
+
+    let delete_preset = move |_| {
+        let name = name.get();
+        set_saved.update(|saved| {
+            saved.retain(|saved| saved.name != name);
+            storage::save(presets::STORAGE_KEY, saved);
+        });
+    };
+
+    let export_presets = move |_| match presets::export(&saved.get()) {
+        Ok(json) => files::download("presets.json", "application/json", &json),
+        Err(err) => set_error.set(Some(err.to_string())),
+    };
+
+    let import_presets = move |ev| {
+        let keygen = keygen.clone();
+        spawn_local(async move {
+            let Some(json) = files::read_text(&ev).await else {
+                return;
+            };
+            match presets::import(&json, &keygen) {
+                Ok(imported) => {
+                    set_error.set(None);
+                    set_saved.update(|saved| {
+                        presets::merge(saved, imported);
+                        storage::save(presets::STORAGE_KEY, saved);
+                    });
+                }
+                Err(err) => set_error.set(Some(err.to_string())),
+            }
+        });
+    };
+
+    view! {
+        <div class="flex flex-col sm:flex-row gap-4 mb-4">
+            <div class="flex-[2]">
+                <SelectField label="Presets" id="preset" on_input=update_saved>
+                    <option value="" selected=move || saved.get().iter().all(|p| p.name != name.get())>
+                        "Custom"
+                    </option>
+                    {move || {
+                        saved
+                            .get()
+                            .into_iter()
+                            .map(|preset| {
+                                let selected = preset.name == name.get();
+                                view! {
+                                    <option value=preset.name.clone() selected=selected>
+                                        {preset.name}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    }}
+                </SelectField>
+            </div>
+            <div class="flex-1">
+                <TextField
+                    label="Preset Name"
+                    id="presetname"
+                    on_change=|_| ()
+                    on_input=move |ev| set_name.set(event_target_value(&ev))
+                    value=name
+                />
+            </div>
+        </div>
+        <div class="flex flex-row flex-wrap gap-4 mb-6">
+            <Button
+                class="text-slate-100 bg-indigo-500 hover:bg-indigo-600 dark:bg-indigo-600 dark:hover:bg-indigo-700"
+                on_click=save_preset
+            >
+                "Save Preset"
+            </Button>
+            <Button
+                class="text-slate-100 bg-rose-600 hover:bg-rose-700 dark:bg-rose-700 dark:hover:bg-rose-800"
+                on_click=delete_preset
+            >
+                "Delete"
+            </Button>
+            <Button class="bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600" on_click=export_presets>
+                "Export"
+            </Button>
+            <label class="shadow-sm rounded-lg px-4 py-2 cursor-pointer bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600">
+                "Import"
+                <input type="file" accept=".json,application/json" class="hidden" on:change=import_presets />
+            </label>
+        </div>
+        {move || {
+            error
+                .get()
+                .map(|err| {
+                    view! {
+                        <div class="mb-6">
+                            <Error>{err}</Error>
+                        </div>
+                    }
+                })
+        }}
     }
 }
@@ -0,0 +1,237 @@
+use std::collections::BTreeSet;
+
+use leptos::*;
+
+use crate::{
+    components::{
+        button::Button,
+        error::Error,
+        fields::{SelectField, TextField},
+        loading::Loading,
+    },
+    files, history, routes,
+};
+
+const KINDS: [&str; 3] = ["Generated", "Validated", "Activated"];
+
+#[component]
+pub fn History() -> impl IntoView {
+    let (search, set_search) = create_signal(routes::query_param("search").unwrap_or_default());
+    let (kind, set_kind) = create_signal("".to_string());
+    let (product, set_product) = create_signal("".to_string());
+    let (channel, set_channel) = create_signal("".to_string());
+    let (error, set_error) = create_signal(None::<String>);
+    // Bumped to reload the history after it's been changed
+    let (changed, set_changed) = create_signal(());
+
+    let entries = create_local_resource(
+        move || changed.get(),
+        |_| async move { history::load().await.map_err(|err| err.to_string()) },
+    );
+
+    let all_entries = move || {
+        entries
+            .get()
+            .and_then(|entries| entries.ok())
+            .unwrap_or_default()
+    };
+
+    let filtered = move || {
+        let search = search.get();
+        let kind = kind.get();
+        let product = product.get();
+        let channel = channel.get();
+        all_entries()
+            .into_iter()
+            .filter(|entry| entry.matches(&search))
+            .filter(|entry| kind.is_empty() || entry.item.kind() == kind)
+            .filter(|entry| product.is_empty() || entry.item.products().contains(&product))
+            .filter(|entry| {
+                channel.trim().is_empty() || entry.item.channel() == Some(channel.trim())
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let products = move || {
+        all_entries()
+            .iter()
+            .flat_map(|entry| entry.item.products())
+            .collect::<BTreeSet<_>>()
+    };
+
+    let delete_entry = move |id: u32| {
+        spawn_local(async move {
+            if let Err(err) = history::delete(id).await {
+                set_error.set(Some(err.to_string()));
+            }
+            set_changed.set(());
+        });
+    };
+
+    let clear_history = move |_| {
+        let confirmed = web_sys::window()
+            .unwrap()
+            .confirm_with_message("Delete the whole history? This can't be undone.")
+            .unwrap_or(false);
+        if !confirmed {
+            return;
+        }
+        spawn_local(async move {
+            if let Err(err) = history::clear().await {
+                set_error.set(Some(err.to_string()));
+            }
+            set_changed.set(());
+        });
+    };
+
+    let export_json = move |_| match history::to_json(&filtered()) {
+        Ok(json) => files::download("history.json", "application/json", &json),
+        Err(err) => set_error.set(Some(err.to_string())),
+    };
+
+    let export_csv = move |_| {
+        files::download("history.csv", "text/csv", &history::to_csv(&filtered()));
+    };
+
+    let rows = move || {
+        filtered()
+            .into_iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let class = if i % 2 == 0 { "" } else { "bg-slate-100 dark:bg-slate-700" };
+                let id = entry.id;
+                let item = &entry.item;
+                view! {
+                    <tr class=class>
+                        <td class="px-4 py-2 whitespace-nowrap">{entry.time()}</td>
+                        <td class="px-4 py-2">{item.kind()}</td>
+                        <td class="px-4 py-2">{item.products().join(", ")}</td>
+                        <td class="font-mono px-4 py-2">
+                            {item.channel().unwrap_or_default().to_string()}
+                        </td>
+                        <td class="font-mono px-4 py-2 break-all">
+                            {item.value()}
+                            <div class="font-sans text-sm text-slate-500 dark:text-slate-400">
+                                {item.details()}
+                            </div>
+                        </td>
+                        <td class="px-4 py-2 text-right">
+                            {id
+                                .map(|id| {
+                                    view! {
+                                        <Button
+                                            class="text-slate-100 bg-rose-600 hover:bg-rose-700 dark:bg-rose-700 dark:hover:bg-rose-800"
+                                            on_click=move |_| delete_entry(id)
+                                        >
+                                            "Delete"
+                                        </Button>
+                                    }
+                                })}
+                        </td>
+                    </tr>
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <div class="mb-4">
+            <TextField
+                label="Search History"
+                id="historysearch"
+                on_change=|_| ()
+                on_input=move |ev| set_search.set(event_target_value(&ev))
+                value=search
+            />
+        </div>
+        <div class="flex flex-col sm:flex-row gap-4 mb-6">
+            <div class="flex-1">
+                <SelectField
+                    label="Kind"
+                    id="historykind"
+                    on_input=move |ev| set_kind.set(event_target_value(&ev))
+                >
+                    <option value="">"All"</option>
+                    {KINDS
+                        .into_iter()
+                        .map(|kind| view! { <option value=kind>{kind}</option> })
+                        .collect::<Vec<_>>()}
+                </SelectField>
+            </div>
+            <div class="flex-[2]">
+                <SelectField
+                    label="Product"
+                    id="historyproduct"
+                    on_input=move |ev| set_product.set(event_target_value(&ev))
+                >
+                    <option value="">"All"</option>
+                    {move || {
+                        let selected = product.get();
+                        products()
+                            .into_iter()
+                            .map(|name| {
+                                view! {
+                                    <option value=name.clone() selected=name == selected>
+                                        {name.clone()}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    }}
+                </SelectField>
+            </div>
+            <div class="flex-1">
+                <TextField
+                    label="Channel ID"
+                    id="historychannel"
+                    on_change=|_| ()
+                    on_input=move |ev| set_channel.set(event_target_value(&ev))
+                    value=channel
+                />
+            </div>
+        </div>
+        <div class="flex flex-row flex-wrap gap-4 mb-6">
+            <Button class="bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600" on_click=export_json>
+                "Export JSON"
+            </Button>
+            <Button class="bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600" on_click=export_csv>
+                "Export CSV"
+            </Button>
+            <Button
+                class="text-slate-100 bg-rose-600 hover:bg-rose-700 dark:bg-rose-700 dark:hover:bg-rose-800"
+                on_click=clear_history
+            >
+                "Clear All"
+            </Button>
+        </div>
+        {move || {
+            error
+                .get()
+                .or_else(|| entries.get().and_then(|entries| entries.err()))
+                .map(|err| {
+                    view! {
+                        <div class="mb-6">
+                            <Error>{err}</Error>
+                        </div>
+                    }
+                })
+        }}
+        <Suspense fallback=move || view! { <Loading /> }>
+            <div class="overflow-x-auto">
+                <table class="w-full text-left">
+                    <thead>
+                        <tr>
+                            <th class="font-semibold px-4 py-2">"Time"</th>
+                            <th class="font-semibold px-4 py-2">"Kind"</th>
+                            <th class="font-semibold px-4 py-2">"Products"</th>
+                            <th class="font-semibold px-4 py-2">"Channel"</th>
+                            <th class="font-semibold px-4 py-2">"Key / ID"</th>
+                            <th></th>
+                        </tr>
+                    </thead>
+                    <tbody>{rows}</tbody>
+                </table>
+            </div>
+        </Suspense>
+    }
+}
@@ -0,0 +1,103 @@
+use leptos::*;
+use leptos_router::{use_navigate, Redirect};
+
+use crate::{
+    components::{button::Button, fields::Label},
+    detect::{self, Detected},
+    routes,
+};
+
+/// The landing page, which works out what's been pasted in and opens the tab for it
+#[component]
+pub fn Home(can_generate: bool) -> impl IntoView {
+    use wasm_bindgen_futures::JsFuture;
+
+    if let Some(path) = routes::legacy_path() {
+        return view! { <Redirect path=path options=routes::replace() /> }.into_view();
+    }
+
+    let (text, set_text) = create_signal("".to_string());
+    let detected = create_memo(move |_| detect::detect(&text.get()));
+    let path = move || {
+        detected
+            .get()
+            .and_then(|detected| detected.path(can_generate))
+    };
+
+    let navigate = use_navigate();
+    let open = {
+        let navigate = navigate.clone();
+        move |_| {
+            if let Some(path) = path() {
+                navigate(&path, Default::default());
+            }
+        }
+    };
+
+    // Goes straight to the tab when the clipboard holds something recognisable, otherwise
+    // leaves it in the box to be looked at
+    let paste_action = create_action(move |_: &()| {
+        let navigate = navigate.clone();
+        async move {
+            let clipboard = web_sys::window().unwrap().navigator().clipboard();
+            let Some(pasted) = JsFuture::from(clipboard.read_text())
+                .await
+                .ok()
+                .and_then(|text| text.as_string())
+            else {
+                return;
+            };
+            let path = detect::detect(&pasted).and_then(|detected| detected.path(can_generate));
+            set_text.set(pasted);
+            if let Some(path) = path {
+                navigate(&path, Default::default());
+            }
+        }
+    });
+
+    let status = move || match detected.get() {
+        _ if text.get().trim().is_empty() => "".to_string(),
+        None => {
+            "Not a product key, installation ID, confirmation ID, product ID or DigitalProductId"
+                .to_string()
+        }
+        Some(detected @ Detected::ProductId { .. }) if !can_generate => format!(
+            "{}. Keys can't be generated in this build.",
+            detected.describe()
+        ),
+        Some(detected) => detected.describe(),
+    };
+
+    view! {
+        <p class="mb-4">
+            "Paste a product key, installation ID, confirmation ID, product ID or DigitalProductId hex to open it on the right tab."
+        </p>
+        <div class="mb-4">
+            <Label for_id="anything">"Anything"</Label>
+            <textarea
+                id="anything"
+                class="block w-full h-24 px-3 py-2 rounded-lg font-mono bg-slate-100 dark:bg-slate-900"
+                prop:value=text
+                on:input=move |ev| set_text.set(event_target_value(&ev))
+            ></textarea>
+        </div>
+        <p class="mb-4">{status}</p>
+        <div class="flex flex-row flex-wrap gap-4">
+            <Button
+                class="bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600"
+                on_click=move |_| paste_action.dispatch(())
+            >
+                "Paste from Clipboard"
+            </Button>
+            <Show when=move || path().is_some()>
+                <Button
+                    class="text-slate-100 bg-indigo-500 hover:bg-indigo-600 dark:bg-indigo-600 dark:hover:bg-indigo-700"
+                    on_click=open.clone()
+                >
+                    "Open"
+                </Button>
+            </Show>
+        </div>
+    }
+        .into_view()
+}
@@ -0,0 +1,305 @@
+use std::time::Duration;
+
+use leptos::{leptos_dom::helpers::TimeoutHandle, *};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        button::Button,
+        error::Error,
+        fields::{CheckBox, Label, NumberField, SelectField, TextField},
+        loading::Loading,
+    },
+    crypto::ProductKey,
+    files,
+    labels::{self, LabelTemplate, SheetOptions},
+    storage, worker,
+};
+
+pub const STORAGE_KEY: &str = "mskey-web.labels";
+
+/// How long to wait for typing to settle before reading the keys, since each is sent to the worker
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// The label sheet form, which the Generate page also fills in with bulk keys
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormState {
+    pub keys: String,
+    pub product: String,
+    pub template: LabelTemplate,
+    pub skip: String,
+    pub outlines: bool,
+}
+
+impl FormState {
+    pub fn load() -> Self {
+        storage::load(STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save(STORAGE_KEY, self);
+    }
+}
+
+/// Splits a line into the key and the machine name after it, if there is one
+fn parse_line(line: &str) -> Option<(String, Option<String>)> {
+    let (key, machine) = match line.split_once([',', '\t']) {
+        Some((key, machine)) => (key, Some(machine.trim().to_string())),
+        None => (line, None),
+    };
+    let key = key.trim().to_uppercase();
+    (!key.is_empty()).then(|| (key, machine.filter(|machine| !machine.is_empty())))
+}
+
+/// Validates each key and works out what goes on its label
+async fn read_labels(text: String) -> Vec<Result<labels::Label, String>> {
+    let lines = text
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| parse_line(line).map(|parsed| (i + 1, parsed)));
+    futures::future::join_all(lines.map(|(number, (key, machine))| async move {
+        let product_key = worker::validate_key(key.clone()).await;
+        let (channel, product_id, bink_ids) = match product_key {
+            Some(ProductKey::Bink1998 {
+                key: decoded,
+                bink_ids,
+                ..
+            }) => {
+                let channel = decoded.channel_id.parse::<u32>().unwrap_or_default();
+                let sequence = decoded.sequence.parse::<u32>().unwrap_or_default();
+                (
+                    channel,
+                    Some(labels::product_id(channel, sequence)),
+                    bink_ids,
+                )
+            }
+            Some(ProductKey::Bink2002 {
+                key: decoded,
+                bink_ids,
+                ..
+            }) => (
+                decoded.channel_id.parse::<u32>().unwrap_or_default(),
+                None,
+                bink_ids,
+            ),
+            _ => return Err(format!("Line {number}: {key} is not a valid product key")),
+        };
+        let mut products = bink_ids.into_keys().collect::<Vec<_>>();
+        products.sort();
+        Ok(labels::Label {
+            key,
+            product: products.join(" / "),
+            channel: format!("{channel:03}"),
+            product_id,
+            machine,
+        })
+    }))
+    .await
+}
+
+#[component]
+pub fn Labels() -> impl IntoView {
+    let initial = FormState::load();
+    let (keys, set_keys) = create_signal(initial.keys);
+    let (product, set_product) = create_signal(initial.product);
+    let (template, set_template) = create_signal(initial.template);
+    let (skip, set_skip) = create_signal(initial.skip);
+    let (outlines, set_outlines) = create_signal(initial.outlines);
+
+    create_effect(move |_| {
+        FormState {
+            keys: keys.get(),
+            product: product.get(),
+            template: template.get(),
+            skip: skip.get(),
+            outlines: outlines.get(),
+        }
+        .save();
+    });
+
+    let update_template = move |ev| {
+        if let Some(template) = LabelTemplate::from_id(&event_target_value(&ev)) {
+            set_template.set(template);
+        }
+    };
+
+    // Every template has room to skip all but its last label
+    let max_skip = LabelTemplate::ALL
+        .into_iter()
+        .map(LabelTemplate::per_sheet)
+        .max()
+        .unwrap_or(1) as u32
+        - 1;
+
+    // The keys as they were when typing last settled. The saved ones are read straight away.
+    let (read_keys, set_read_keys) = create_signal(keys.get_untracked());
+    create_effect(move |pending: Option<Option<TimeoutHandle>>| {
+        let keys = keys.get();
+        match pending {
+            None => return None,
+            Some(Some(pending)) => pending.clear(),
+            Some(None) => {}
+        }
+        set_timeout_with_handle(move || set_read_keys.set(keys), DEBOUNCE).ok()
+    });
+
+    let read = create_local_resource(move || read_keys.get(), read_labels);
+
+    let options = move || SheetOptions {
+        template: template.get(),
+        skip: skip.get().trim().parse().unwrap_or_default(),
+        outlines: outlines.get(),
+    };
+
+    let sheet_labels = move || {
+        let product = product.get();
+        read.get()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|label| {
+                if product.trim().is_empty() {
+                    label
+                } else {
+                    labels::Label {
+                        product: product.trim().to_string(),
+                        ..label
+                    }
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let errors = move || {
+        read.get()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(Result::err)
+            .collect::<Vec<_>>()
+    };
+
+    let pages = create_memo(move |_| labels::to_svg(options(), &sheet_labels()));
+
+    let download_pdf = move |_| {
+        let pdf = labels::to_pdf(options(), &sheet_labels());
+        files::download("labels.pdf", "application/pdf", &pdf);
+    };
+
+    let download_svg = move |_| {
+        for (i, page) in pages.get().iter().enumerate() {
+            files::download(&format!("labels-{}.svg", i + 1), "image/svg+xml", page);
+        }
+    };
+
+    view! {
+        <p class="mb-4">
+            "Print keys onto sheets of adhesive labels, with a Code 128 barcode for scanning them back in. Put one key on each line, optionally followed by a comma and the name of the machine it's for."
+        </p>
+        <div class="mb-4">
+            <Label for_id="labelkeys">"Keys"</Label>
+            <textarea
+                id="labelkeys"
+                class="block w-full h-48 px-3 py-2 rounded-lg font-mono bg-slate-100 dark:bg-slate-900"
+                placeholder="XXXXX-XXXXX-XXXXX-XXXXX-XXXXX, LAB-PC-01"
+                prop:value=keys
+                on:input=move |ev| set_keys.set(event_target_value(&ev))
+            ></textarea>
+        </div>
+        <div class="mb-4">
+            <TextField
+                label="Product Name (leave blank to use the products the key is valid for)"
+                id="labelproduct"
+                on_change=|_| ()
+                on_input=move |ev| set_product.set(event_target_value(&ev))
+                value=product
+            />
+        </div>
+        <div class="flex flex-col sm:flex-row sm:items-end gap-4 mb-6">
+            <div class="flex-[2]">
+                <SelectField label="Label Template" id="labeltemplate" on_input=update_template>
+                    {move || {
+                        let selected = template.get();
+                        LabelTemplate::ALL
+                            .into_iter()
+                            .map(|template| {
+                                view! {
+                                    <option value=template.id() selected=template == selected>
+                                        {template.to_string()}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    }}
+                </SelectField>
+            </div>
+            <div class="flex-1">
+                <NumberField
+                    label="Skip Labels"
+                    id="labelskip"
+                    min=0
+                    max=max_skip
+                    value=skip
+                    on_input=move |ev| set_skip.set(event_target_value(&ev))
+                    placeholder="0"
+                />
+            </div>
+            <div class="flex-1">
+                <CheckBox
+                    label="Outlines"
+                    id="labeloutlines"
+                    checked=outlines
+                    on_change=move |ev| set_outlines.set(event_target_checked(&ev))
+                />
+            </div>
+        </div>
+        {move || {
+            errors()
+                .into_iter()
+                .map(|err| {
+                    view! {
+                        <div class="mb-4">
+                            <Error>{err}</Error>
+                        </div>
+                    }
+                })
+                .collect::<Vec<_>>()
+        }}
+        <div class="flex flex-row flex-wrap items-center gap-4 mb-6">
+            <Button
+                class="text-slate-100 bg-indigo-500 hover:bg-indigo-600 dark:bg-indigo-600 dark:hover:bg-indigo-700"
+                on_click=download_pdf
+            >
+                "Download PDF"
+            </Button>
+            <Button class="bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600" on_click=download_svg>
+                "Download SVG"
+            </Button>
+            <Suspense fallback=move || view! { <Loading /> }>
+                {move || {
+                    let labels = sheet_labels().len();
+                    let sheets = pages.get().len();
+                    format!(
+                        "{labels} label{} on {sheets} sheet{}",
+                        if labels == 1 { "" } else { "s" },
+                        if sheets == 1 { "" } else { "s" },
+                    )
+                }}
+            </Suspense>
+        </div>
+        {move || {
+            pages
+                .get()
+                .into_iter()
+                .next()
+                .map(|page| {
+                    view! {
+                        <div
+                            class="rounded-lg overflow-hidden shadow [&>svg]:w-full [&>svg]:h-auto"
+                            inner_html=page
+                        ></div>
+                    }
+                })
+        }}
+    }
+}
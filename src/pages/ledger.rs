@@ -0,0 +1,233 @@
+use leptos::*;
+
+use crate::{
+    components::{
+        button::Button,
+        error::Error,
+        fields::{Label, TextField},
+        warning::Warning,
+    },
+    files,
+    ledger::Ledger,
+};
+
+/// The ledger page, for looking through, sharing and pruning the issued sequences
+#[component]
+pub fn LedgerPage() -> impl IntoView {
+    let (ledger, set_ledger) = create_signal(Ledger::load());
+    let (search, set_search) = create_signal("".to_string());
+    let (message, set_message) = create_signal(None::<String>);
+    let (error, set_error) = create_signal(None::<String>);
+
+    let export_ledger = move |_| match ledger.get().export() {
+        Ok(json) => files::download("ledger.json", "application/json", &json),
+        Err(err) => set_error.set(Some(err.to_string())),
+    };
+
+    let import_ledger = move |ev| {
+        spawn_local(async move {
+            let Some(json) = files::read_text(&ev).await else {
+                return;
+            };
+            match Ledger::import(&json) {
+                Ok(imported) => {
+                    set_error.set(None);
+                    // Merge into what's stored, in case another tab has changed it
+                    let mut latest = Ledger::load();
+                    let added = latest.merge(imported);
+                    latest.save();
+                    set_ledger.set(latest);
+                    set_message.set(Some(format!("Imported {added} new sequences")));
+                }
+                Err(err) => set_error.set(Some(err.to_string())),
+            }
+        });
+    };
+
+    let clear_ledger = move |_| {
+        let confirmed = web_sys::window()
+            .unwrap()
+            .confirm_with_message("Forget every issued sequence? This can't be undone.")
+            .unwrap_or(false);
+        if confirmed {
+            set_ledger.update(|ledger| {
+                ledger.clear();
+                ledger.save();
+            });
+        }
+    };
+
+    let rows = move || {
+        let search = search.get();
+        let search = search.trim().to_lowercase();
+        let mut entries = ledger.get().entries().to_vec();
+        entries.sort_by(|a, b| {
+            (&a.bink, a.channel, a.sequence).cmp(&(&b.bink, b.channel, b.sequence))
+        });
+        entries
+            .into_iter()
+            .filter(|entry| {
+                search.is_empty()
+                    || entry.product.to_lowercase().contains(&search)
+                    || entry.bink.eq_ignore_ascii_case(&search)
+                    || format!("{:03}", entry.channel) == search
+                    || entry.sequence.to_string() == search
+            })
+            .enumerate()
+            .map(|(i, entry)| {
+                let class = if i % 2 == 0 { "" } else { "bg-slate-100 dark:bg-slate-700" };
+                let remove = entry.clone();
+                view! {
+                    <tr class=class>
+                        <td class="font-mono px-4 py-2">{entry.bink.clone()}</td>
+                        <td class="font-mono px-4 py-2">{format!("{:03}", entry.channel)}</td>
+                        <td class="font-mono px-4 py-2">{format!("{:06}", entry.sequence)}</td>
+                        <td class="px-4 py-2">{entry.product.clone()}</td>
+                        <td class="px-4 py-2 whitespace-nowrap">{entry.issued_at()}</td>
+                        <td class="px-4 py-2 text-right">
+                            <Button
+                                class="text-slate-100 bg-rose-600 hover:bg-rose-700 dark:bg-rose-700 dark:hover:bg-rose-800"
+                                on_click=move |_| {
+                                    set_ledger.update(|ledger| {
+                                        ledger.remove(&remove);
+                                        ledger.save();
+                                    });
+                                }
+                            >
+                                "Delete"
+                            </Button>
+                        </td>
+                    </tr>
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <p class="mb-4">
+            "Sequences issued for BINK1998 keys are recorded here, so the same BINK and channel never get a sequence twice. Export the ledger and import it elsewhere to share one record between several people."
+        </p>
+        <div class="mb-4">
+            <TextField
+                label="Search Ledger"
+                id="ledgersearch"
+                on_change=|_| ()
+                on_input=move |ev| set_search.set(event_target_value(&ev))
+                value=search
+            />
+        </div>
+        <div class="flex flex-row flex-wrap gap-4 mb-6">
+            <Button class="bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600" on_click=export_ledger>
+                "Export"
+            </Button>
+            <label class="shadow-sm rounded-lg px-4 py-2 cursor-pointer bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600">
+                "Import"
+                <input type="file" accept=".json,application/json" class="hidden" on:change=import_ledger />
+            </label>
+            <Button
+                class="text-slate-100 bg-rose-600 hover:bg-rose-700 dark:bg-rose-700 dark:hover:bg-rose-800"
+                on_click=clear_ledger
+            >
+                "Clear All"
+            </Button>
+        </div>
+        {move || {
+            error
+                .get()
+                .map(|err| {
+                    view! {
+                        <div class="mb-6">
+                            <Error>{err}</Error>
+                        </div>
+                    }
+                })
+        }}
+        {move || message.get().map(|message| view! { <p class="mb-6">{message}</p> })}
+        <div class="overflow-x-auto">
+            <table class="w-full text-left">
+                <thead>
+                    <tr>
+                        <th class="font-semibold px-4 py-2">"BINK"</th>
+                        <th class="font-semibold px-4 py-2">"Channel"</th>
+                        <th class="font-semibold px-4 py-2">"Sequence"</th>
+                        <th class="font-semibold px-4 py-2">"Product"</th>
+                        <th class="font-semibold px-4 py-2">"Issued"</th>
+                        <th></th>
+                    </tr>
+                </thead>
+                <tbody>{rows}</tbody>
+            </table>
+        </div>
+    }
+}
+
+/// Ledger controls for the Generate page: picking unused sequences and warning about reused ones.
+/// The Generate page records the sequences of the keys it hands out in `ledger`.
+#[component]
+pub fn SequenceLedger(
+    #[prop(into)] bink_id: Signal<u8>,
+    #[prop(into)] channel_id: Signal<String>,
+    #[prop(into)] sequence: Signal<String>,
+    set_sequence: WriteSignal<String>,
+    ledger: RwSignal<Ledger>,
+) -> impl IntoView {
+    let channel = move || channel_id.get().trim().parse::<u32>().ok();
+
+    let next_free = move |_| {
+        let Some(channel) = channel() else {
+            return;
+        };
+        if let Some(next) = ledger.get().next_free(bink_id.get(), channel) {
+            set_sequence.set(next.to_string());
+        }
+    };
+
+    let random_free = move |_| {
+        let Some(channel) = channel() else {
+            return;
+        };
+        if let Some(random) = ledger
+            .get()
+            .random_free(bink_id.get(), channel, js_sys::Math::random)
+        {
+            set_sequence.set(random.to_string());
+        }
+    };
+
+    let reused = move || {
+        let channel = channel()?;
+        let sequence = sequence.get().trim().parse::<u32>().ok()?;
+        ledger.with(|ledger| {
+            ledger.find(bink_id.get(), channel, sequence).map(|entry| {
+                format!(
+                    "Sequence {sequence:06} on channel {channel:03} was already issued for {} \
+                    on {}",
+                    entry.product,
+                    entry.issued_at()
+                )
+            })
+        })
+    };
+
+    view! {
+        <Label>"Sequence Ledger"</Label>
+        <div class="flex flex-row flex-wrap gap-4 mb-4">
+            <Button class="bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600" on_click=next_free>
+                "Next Free"
+            </Button>
+            <Button class="bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600" on_click=random_free>
+                "Random Unused"
+            </Button>
+        </div>
+        {move || {
+            reused()
+                .map(|reused| {
+                    view! {
+                        <div class="mb-4">
+                            <Warning>{reused}</Warning>
+                        </div>
+                    }
+                })
+        }}
+    }
+}
@@ -1,3 +1,11 @@
 pub mod activate;
+pub mod custom;
+pub mod dataset;
 pub mod generate;
+pub mod history;
+pub mod home;
+pub mod labels;
+pub mod ledger;
+pub mod scan;
+pub mod settings;
 pub mod validate;
@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use leptos::{leptos_dom::helpers::TimeoutHandle, *};
+use leptos_router::A;
+
+use crate::{
+    components::{fields::Label, loading::Loading},
+    crypto::ProductKey,
+    files, routes,
+    scanner::{self, Found, Hit},
+    worker,
+};
+
+/// How long to wait for typing to settle before scanning, since every hit is sent to the worker
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// What came of checking a hit
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Outcome {
+    /// The products the key is valid for
+    Valid(Vec<String>),
+    Invalid,
+    ConfirmationId(String),
+    NoConfirmationId,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ScanResult {
+    source: String,
+    hit: Hit,
+    outcome: Outcome,
+}
+
+async fn check(source: String, hit: Hit) -> ScanResult {
+    let outcome = match &hit.found {
+        Found::Key(key) => match worker::validate_key(key.clone()).await {
+            Some(ProductKey::Bink1998 { bink_ids, .. } | ProductKey::Bink2002 { bink_ids, .. }) => {
+                let mut products = bink_ids.into_keys().collect::<Vec<_>>();
+                products.sort();
+                Outcome::Valid(products)
+            }
+            _ => Outcome::Invalid,
+        },
+        Found::InstallationId(installation_id) => {
+            match worker::confirmation_id(installation_id.clone()).await {
+                Some(confirmation_id) => Outcome::ConfirmationId(confirmation_id),
+                None => Outcome::NoConfirmationId,
+            }
+        }
+    };
+    ScanResult {
+        source,
+        hit,
+        outcome,
+    }
+}
+
+/// Scans pasted text and the picked files, then checks everything that was found
+async fn scan_all(text: String, uploaded: Vec<(String, String)>) -> Vec<ScanResult> {
+    let sources = std::iter::once(("Pasted text".to_string(), text)).chain(uploaded);
+    let checks = sources
+        .flat_map(|(source, text)| {
+            scanner::scan(&text)
+                .into_iter()
+                .map(move |hit| check(source.clone(), hit))
+        })
+        .collect::<Vec<_>>();
+    futures::future::join_all(checks).await
+}
+
+#[component]
+pub fn Scan() -> impl IntoView {
+    let (text, set_text) = create_signal("".to_string());
+    let (uploaded, set_uploaded) = create_signal(Vec::<(String, String)>::new());
+
+    // The text as it was when typing last settled
+    let (scanned_text, set_scanned_text) = create_signal(String::new());
+    create_effect(move |pending: Option<Option<TimeoutHandle>>| {
+        let text = text.get();
+        if let Some(Some(pending)) = pending {
+            pending.clear();
+        }
+        set_timeout_with_handle(move || set_scanned_text.set(text), DEBOUNCE).ok()
+    });
+
+    let results = create_local_resource(
+        move || (scanned_text.get(), uploaded.get()),
+        |(text, uploaded)| scan_all(text, uploaded),
+    );
+
+    let load_files = move |ev| {
+        spawn_local(async move {
+            set_uploaded.set(files::read_all_text(&ev).await);
+        });
+    };
+
+    let summary = move || {
+        let results = results.get().unwrap_or_default();
+        let count = |kind: fn(&Outcome) -> bool| {
+            results
+                .iter()
+                .filter(|result| kind(&result.outcome))
+                .count()
+        };
+        format!(
+            "{} valid and {} invalid keys, {} installation IDs with confirmation IDs and {} without",
+            count(|outcome| matches!(outcome, Outcome::Valid(_))),
+            count(|outcome| *outcome == Outcome::Invalid),
+            count(|outcome| matches!(outcome, Outcome::ConfirmationId(_))),
+            count(|outcome| *outcome == Outcome::NoConfirmationId),
+        )
+    };
+
+    let rows = move || {
+        results
+            .get()
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+            .map(|(i, result)| {
+                let class = if i % 2 == 0 { "" } else { "bg-slate-100 dark:bg-slate-700" };
+                let hit = result.hit;
+                let outcome = match (result.outcome, hit.found) {
+                    (Outcome::Valid(products), Found::Key(key)) => {
+                        view! {
+                            <A href=routes::validate_path(&key) class="hover:underline">
+                                {products.join(", ")}
+                            </A>
+                        }
+                            .into_view()
+                    }
+                    (Outcome::ConfirmationId(confirmation_id), Found::InstallationId(iid)) => {
+                        view! {
+                            <A href=format!("/activate?iid={iid}") class="font-mono hover:underline">
+                                {confirmation_id}
+                            </A>
+                        }
+                            .into_view()
+                    }
+                    (Outcome::Invalid, _) => {
+                        view! { <span class="text-rose-600 dark:text-rose-400">"Invalid key"</span> }
+                            .into_view()
+                    }
+                    _ => {
+                        view! {
+                            <span class="text-rose-600 dark:text-rose-400">
+                                "Invalid installation ID"
+                            </span>
+                        }
+                            .into_view()
+                    }
+                };
+                view! {
+                    <tr class=class>
+                        <td class="px-4 py-2 whitespace-nowrap">
+                            {format!("{}:{}", result.source, hit.line)}
+                        </td>
+                        <td class="font-mono px-4 py-2 break-all">
+                            {hit.before}
+                            <mark class="rounded px-1 bg-amber-200 dark:bg-amber-700 dark:text-slate-100">
+                                {hit.text}
+                            </mark>
+                            {hit.after}
+                        </td>
+                        <td class="px-4 py-2">{outcome}</td>
+                    </tr>
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <p class="mb-4">
+            "Paste an email, setup log or inventory export, or pick some files, to pull out everything that looks like a product key or an installation ID. Keys are validated and confirmation IDs are worked out for installation IDs."
+        </p>
+        <div class="mb-4">
+            <Label for_id="scantext">"Text"</Label>
+            <textarea
+                id="scantext"
+                class="block w-full h-48 px-3 py-2 rounded-lg font-mono bg-slate-100 dark:bg-slate-900"
+                prop:value=text
+                on:input=move |ev| set_text.set(event_target_value(&ev))
+            ></textarea>
+        </div>
+        <div class="flex flex-row flex-wrap items-center gap-4 mb-6">
+            <label class="shadow-sm rounded-lg px-4 py-2 cursor-pointer bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600">
+                "Scan Files"
+                <input type="file" multiple class="hidden" on:change=load_files />
+            </label>
+            <span>
+                {move || {
+                    uploaded
+                        .get()
+                        .into_iter()
+                        .map(|(name, _)| name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                }}
+            </span>
+        </div>
+        <Suspense fallback=move || view! { <Loading /> }>
+            <p class="mb-4">{summary}</p>
+            <div class="overflow-x-auto">
+                <table class="w-full text-left">
+                    <thead>
+                        <tr>
+                            <th class="font-semibold px-4 py-2">"Line"</th>
+                            <th class="font-semibold px-4 py-2">"Found"</th>
+                            <th class="font-semibold px-4 py-2">"Result"</th>
+                        </tr>
+                    </thead>
+                    <tbody>{rows}</tbody>
+                </table>
+            </div>
+        </Suspense>
+    }
+}
@@ -0,0 +1,127 @@
+use leptos::*;
+
+use crate::{
+    answer_files,
+    components::{
+        button::Button,
+        fields::{CheckBox, Label, SelectField},
+        pidgen2_keys,
+    },
+    pages::{activate, generate, labels, validate},
+    settings::{self, use_settings, NumberFormat},
+    storage,
+};
+
+/// Everything the app remembers between visits, other than saved custom BINKs, presets and
+/// the sequence ledger
+const SAVED_STATE: [(&str, &str); 7] = [
+    ("Generate form", generate::STORAGE_KEY),
+    ("PIDGEN2 form", pidgen2_keys::STORAGE_KEY),
+    ("Validate form", validate::STORAGE_KEY),
+    ("Activate form", activate::STORAGE_KEY),
+    ("Label sheet", labels::STORAGE_KEY),
+    ("Answer file", answer_files::STORAGE_KEY),
+    ("Display settings", settings::STORAGE_KEY),
+];
+
+#[component]
+pub fn Settings() -> impl IntoView {
+    let settings = use_settings();
+    let (dashed_keys, set_dashed_keys) = create_signal(settings.get_untracked().dashed_keys);
+    // Bumped whenever something is reset, to re-read what's in storage
+    let (reset, set_reset) = create_signal(());
+
+    let update_dashed_keys = move |ev| {
+        let dashed_keys = event_target_checked(&ev);
+        set_dashed_keys.set(dashed_keys);
+        settings.update(|settings| settings.dashed_keys = dashed_keys);
+    };
+
+    let update_number_format = move |ev| {
+        if let Some(number_format) = NumberFormat::from_id(&event_target_value(&ev)) {
+            settings.update(|settings| settings.number_format = number_format);
+        }
+    };
+
+    let reset_item = move |key: &'static str| {
+        if key == settings::STORAGE_KEY {
+            settings.set(settings::Settings::default());
+            set_dashed_keys.set(settings::Settings::default().dashed_keys);
+        }
+        storage::remove(key);
+        set_reset.set(());
+    };
+
+    let saved_state = move || {
+        reset.get();
+        settings.track();
+        SAVED_STATE
+            .into_iter()
+            .enumerate()
+            .map(|(i, (label, key))| {
+                let class = if i % 2 == 0 { "" } else { "bg-slate-100 dark:bg-slate-700" };
+                let value = storage::load::<serde_json::Value>(key)
+                    .map(|value| value.to_string())
+                    .unwrap_or_else(|| "Nothing saved".to_string());
+                view! {
+                    <tr class=class>
+                        <th class="font-semibold px-4 py-2">{label}</th>
+                        <td class="font-mono px-4 py-2 break-all">{value}</td>
+                        <td class="px-4 py-2 text-right">
+                            <Button
+                                class="text-slate-100 bg-rose-600 hover:bg-rose-700 dark:bg-rose-700 dark:hover:bg-rose-800"
+                                on_click=move |_| reset_item(key)
+                            >
+                                "Reset"
+                            </Button>
+                        </td>
+                    </tr>
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <div class="flex flex-col sm:flex-row gap-4 mb-6">
+            <div class="flex-1">
+                <CheckBox
+                    label="Dashed Keys"
+                    id="dashedkeys"
+                    checked=dashed_keys
+                    on_change=update_dashed_keys
+                />
+            </div>
+            <div class="flex-1">
+                <SelectField label="Key Details" id="numberformat" on_input=update_number_format>
+                    {move || {
+                        let selected = settings.get().number_format;
+                        NumberFormat::ALL
+                            .into_iter()
+                            .map(|format| {
+                                view! {
+                                    <option value=format.id() selected=format == selected>
+                                        {format.to_string()}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    }}
+                </SelectField>
+            </div>
+        </div>
+        <Label>"Saved in This Browser"</Label>
+        <table class="w-full text-left mb-4">
+            <tbody>{saved_state}</tbody>
+        </table>
+        <Button
+            class="text-slate-100 bg-rose-600 hover:bg-rose-700 dark:bg-rose-700 dark:hover:bg-rose-800"
+            on_click=move |_| {
+                for (_, key) in SAVED_STATE {
+                    reset_item(key);
+                }
+            }
+        >
+            "Reset Everything"
+        </Button>
+    }
+}
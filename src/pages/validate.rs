@@ -1,74 +1,144 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    rc::Rc,
+};
+
 use leptos::*;
-use leptos_workers::worker;
-use wasm_bindgen::JsValue;
-use web_sys::{Url, UrlSearchParams};
+use leptos_router::use_params_map;
 
 use crate::{
+    answer_files::{self, FoundKey},
     components::{
-        bink1998_details::Bink1998Details, bink2002_details::Bink2002Details, error::Error,
-        fields::TextField,
+        bink1998_details::Bink1998Details,
+        bink2002_details::Bink2002Details,
+        error::Error,
+        fields::{CheckBox, SelectField, TextField},
+        key_editor::{KeyEditor, KeyEdits},
+        loading::Loading,
+        pidgen2_details::{Pidgen2CdDetails, Pidgen2OemDetails},
+        qr_code::QrCode,
     },
-    crypto::{self, ProductKey},
+    crypto::{KeyTool, ProductKey, PIDGEN2_CD, PIDGEN2_OEM},
+    digital_product_id::DigitalProductId,
+    files,
+    history::{self, HistoryItem},
+    routes::{self, Tab},
+    settings::use_settings,
+    storage, worker,
 };
 
-#[worker]
-async fn validate_key(key: String) -> ProductKey {
-    let key_tool = crypto::KeyTool::new();
-    let product_key = key_tool.validate_key(&key);
-    product_key.unwrap_or(ProductKey::Invalid)
-}
+pub const STORAGE_KEY: &str = "mskey-web.validate";
+
+/// Characters in a whole product key, leaving out the dashes
+const KEY_LENGTH: usize = 25;
 
 #[component]
-pub fn Validate() -> impl IntoView {
-    let search = web_sys::window().unwrap().location().search().unwrap();
-    let search = UrlSearchParams::new_with_str(&search).unwrap();
-
-    let key = if let Some(key) = search.get("k") {
-        key
-    } else {
-        "".to_string()
-    };
+pub fn Validate(keygen: Rc<KeyTool>) -> impl IntoView {
+    // The key in the path is percent-encoded by `routes::validate_path`
+    let key = use_params_map()
+        .with_untracked(|params| params.get("key").cloned())
+        .map(|key| {
+            js_sys::decode_uri_component(&key)
+                .map(String::from)
+                .unwrap_or(key)
+        })
+        .or_else(|| storage::load(STORAGE_KEY))
+        .unwrap_or_default();
 
     let (product_key, set_product_key) = create_signal(key);
+    // The key restored from the link or the last visit was recorded when it was first entered
+    let entered = store_value(false);
 
     let update_product_key = move |ev| {
-        set_product_key.set(event_target_value(&ev));
+        let key = event_target_value(&ev);
+        storage::save(STORAGE_KEY, &key);
+        entered.set_value(true);
+        set_product_key.set(key);
     };
 
+    let recovered_key = Callback::new(move |key: String| {
+        storage::save(STORAGE_KEY, &key);
+        entered.set_value(true);
+        set_product_key.set(key);
+        window().scroll_to_with_x_and_y(0.0, 0.0);
+    });
+
     let validation_response = create_local_resource(
         move || (product_key.get()),
-        |key| async move {
+        move |key| async move {
             if key.is_empty() {
+                routes::replace_url_on(Tab::Validate, "/validate", &[]);
                 Ok(ProductKey::Empty)
             } else {
-                let product_key = validate_key(key.clone()).await;
-                if let Ok(ProductKey::Bink1998{..}) | Ok(ProductKey::Bink2002 {..}) = product_key {
-                    let url = web_sys::window().unwrap().location().href().unwrap();
-                    let url = Url::new(&url).unwrap();
-                    url.set_search(&format!("?validate&k={}", key.trim()));
-                    let url = url.href();
-                    let _ = web_sys::window()
-                        .unwrap()
-                        .history()
-                        .unwrap()
-                        .replace_state_with_url(&JsValue::NULL, "", Some(&url));
+                let product_key = worker::validate_key(key.clone()).await.ok_or(());
+                // Only keys that check out are worth sharing as a link
+                let valid = matches!(
+                    product_key,
+                    Ok(ProductKey::Bink1998 { .. })
+                        | Ok(ProductKey::Bink2002 { .. })
+                        | Ok(ProductKey::Pidgen2Cd { .. })
+                        | Ok(ProductKey::Pidgen2Oem { .. })
+                );
+                // The page may have been left while the worker was busy
+                if valid {
+                    routes::replace_url_on(Tab::Validate, &routes::validate_path(&key), &[]);
+                } else {
+                    routes::replace_url_on(Tab::Validate, "/validate", &[]);
                 }
-                else {
-                    let url = web_sys::window().unwrap().location().href().unwrap();
-                    let url = Url::new(&url).unwrap();
-                    url.set_search("?validate");
-                    let url = url.href();
-                    let _ = web_sys::window()
-                        .unwrap()
-                        .history()
-                        .unwrap()
-                        .replace_state_with_url(&JsValue::NULL, "", Some(&url));
+                // Skip the invalid results from keys that are still being typed in
+                let complete = key.chars().filter(char::is_ascii_alphanumeric).count() >= KEY_LENGTH;
+                if let Ok(result) = &product_key {
+                    if entered.get_value() && (valid || complete) {
+                        history::record(HistoryItem::validated(&key, result));
+                    }
                 }
                 product_key
             }
         },
     );
 
+    let settings = use_settings();
+    let formatted_key =
+        Signal::derive(move || settings.get().format_key(product_key.get().trim()));
+
+    // Re-signing needs a private key, which validate-only builds don't have
+    let key_edits = {
+        let keygen = keygen.clone();
+        move |decoded: &ProductKey| {
+            keygen
+                .can_generate_any()
+                .then(|| KeyEdits::new(decoded))
+                .flatten()
+        }
+    };
+    // The Edit column is left out of the details when there's no private key for the key's BINK
+    let editable = {
+        let keygen = keygen.clone();
+        move |bink_ids: &HashMap<String, u8>, edits: Option<KeyEdits>| {
+            edits.filter(|_| bink_ids.values().any(|bink_id| keygen.can_generate(*bink_id)))
+        }
+    };
+    let key_editor = {
+        let keygen = keygen.clone();
+        move |decoded: ProductKey, edits: Option<KeyEdits>| {
+            edits.map(|edits| {
+                view! {
+                    <h2 class="mt-10 mb-4 font-bold text-xl">"Edit and Re-sign"</h2>
+                    <KeyEditor
+                        keygen=keygen.clone()
+                        original=product_key.get_untracked().trim().to_string()
+                        key=decoded
+                        edits=edits
+                        on_key=recovered_key
+                    />
+                }
+            })
+        }
+    };
+
+    // The dashes are part of a PIDGEN2 key, so it's always shown as typed
+    let trimmed_key = Signal::derive(move || product_key.get().trim().to_string());
+
     view! {
         <div class="mb-4">
             <TextField
@@ -92,17 +162,59 @@ pub fn Validate() -> impl IntoView {
                         </div>
                     }
                 }
-                Some(Ok(ProductKey::Bink1998 { key, bink_ids })) => {
+                Some(Ok(ProductKey::Bink1998 { key, bink_ids, channel_kinds })) => {
+                    let decoded = ProductKey::Bink1998 {
+                        key: key.clone(),
+                        bink_ids: bink_ids.clone(),
+                        channel_kinds: channel_kinds.clone(),
+                    };
+                    let edits = key_edits(&decoded);
+                    view! {
+                        <div>
+                            <Bink1998Details
+                                key=key
+                                edits=editable(&bink_ids, edits)
+                                bink_ids=bink_ids
+                                channel_kinds=channel_kinds
+                            />
+                            <QrCode value=formatted_key />
+                            {key_editor(decoded, edits)}
+                        </div>
+                    }
+                }
+                Some(Ok(ProductKey::Bink2002 { key, bink_ids, channel_kinds })) => {
+                    let decoded = ProductKey::Bink2002 {
+                        key: key.clone(),
+                        bink_ids: bink_ids.clone(),
+                        channel_kinds: channel_kinds.clone(),
+                    };
+                    let edits = key_edits(&decoded);
                     view! {
                         <div>
-                            <Bink1998Details key=key bink_ids=bink_ids />
+                            <Bink2002Details
+                                key=key
+                                edits=editable(&bink_ids, edits)
+                                bink_ids=bink_ids
+                                channel_kinds=channel_kinds
+                            />
+                            <QrCode value=formatted_key />
+                            {key_editor(decoded, edits)}
                         </div>
                     }
                 }
-                Some(Ok(ProductKey::Bink2002 { key, bink_ids })) => {
+                Some(Ok(ProductKey::Pidgen2Cd { key })) => {
                     view! {
                         <div>
-                            <Bink2002Details key=key bink_ids=bink_ids />
+                            <Pidgen2CdDetails key=key />
+                            <QrCode value=trimmed_key />
+                        </div>
+                    }
+                }
+                Some(Ok(ProductKey::Pidgen2Oem { key })) => {
+                    view! {
+                        <div>
+                            <Pidgen2OemDetails key=key />
+                            <QrCode value=trimmed_key />
                         </div>
                     }
                 }
@@ -115,21 +227,267 @@ pub fn Validate() -> impl IntoView {
                 }
             }}
         </Suspense>
+        <h2 class="mt-10 mb-4 font-bold text-xl">"Recover from DigitalProductId"</h2>
+        <DigitalProductIdDecoder on_key=recovered_key />
+        <h2 class="mt-10 mb-4 font-bold text-xl">"Check Answer Files"</h2>
+        <AnswerFileCheck keygen=keygen.clone() />
     }
 }
 
+/// Digs the key out of a `DigitalProductId` registry value and hands it over to be validated
 #[component]
-fn Loading() -> impl IntoView {
+fn DigitalProductIdDecoder(on_key: Callback<String>) -> impl IntoView {
+    let (text, set_text) = create_signal("".to_string());
+
+    let decoded = create_memo(move |_| {
+        let text = text.get();
+        (!text.trim().is_empty())
+            .then(|| DigitalProductId::parse(&text).map_err(|err| err.to_string()))
+    });
+
+    create_effect(move |_| {
+        if let Some(Ok(decoded)) = decoded.get() {
+            on_key.call(decoded.key);
+        }
+    });
+
+    let load_file = move |ev| {
+        spawn_local(async move {
+            if let Some(text) = files::read_text(&ev).await {
+                set_text.set(text);
+            }
+        });
+    };
+
     view! {
-        <div class="flex items-center justify-center pt-4">
-            <div
-                class="inline-block h-8 w-8 animate-spin rounded-full border-4 border-solid border-current border-e-transparent align-[-0.125em] text-surface motion-reduce:animate-[spin_1.5s_linear_infinite]"
-                role="status"
-            >
-                <span class="!absolute !-m-px !h-px !w-px !overflow-hidden !whitespace-nowrap !border-0 !p-0 ![clip:rect(0,0,0,0)]">
-                    Loading...
-                </span>
+        <p class="mb-4">
+            "Paste the DigitalProductId value from HKLM\\SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion as hex, or open a .reg export of the key. The key it holds is validated at the top of the page."
+        </p>
+        <textarea
+            class="block w-full h-32 mb-4 px-3 py-2 rounded-lg font-mono bg-slate-100 dark:bg-slate-900"
+            placeholder="a4,00,00,00,03,00,00,00,35,35,32,37,34,2d,..."
+            prop:value=text
+            on:input=move |ev| set_text.set(event_target_value(&ev))
+        ></textarea>
+        <label class="inline-block mb-4 shadow-sm rounded-lg px-4 py-2 cursor-pointer bg-slate-200 hover:bg-slate-300 dark:bg-slate-700 dark:hover:bg-slate-600">
+            "Open .reg File"
+            <input type="file" accept=".reg,.txt" class="hidden" on:change=load_file />
+        </label>
+        {move || match decoded.get() {
+            None => view! { <div></div> },
+            Some(Err(err)) => view! { <div><Error>{err}</Error></div> },
+            Some(Ok(decoded)) => {
+                view! {
+                    <div>
+                        <table class="w-full text-left">
+                            <tbody>
+                                <tr>
+                                    <th class="font-semibold px-4 py-2">"Product Key"</th>
+                                    <td class="font-mono px-4 py-2">{decoded.key}</td>
+                                </tr>
+                                <tr class="bg-slate-100 dark:bg-slate-700">
+                                    <th class="font-semibold px-4 py-2">"Product ID"</th>
+                                    <td class="font-mono px-4 py-2">{decoded.product_id}</td>
+                                </tr>
+                            </tbody>
+                        </table>
+                    </div>
+                }
+            }
+        }}
+    }
+}
+
+/// A key from an answer file, with the products it's valid for, or `None` if it isn't valid
+#[derive(Clone, Debug)]
+struct CheckedKey {
+    file: String,
+    found: FoundKey,
+    products: Option<Vec<String>>,
+}
+
+async fn check_key(file: String, found: FoundKey) -> CheckedKey {
+    let products = match worker::validate_key(found.key.clone()).await {
+        Some(ProductKey::Bink1998 { bink_ids, .. } | ProductKey::Bink2002 { bink_ids, .. }) => {
+            let mut products = bink_ids.into_keys().collect::<Vec<_>>();
+            products.sort();
+            Some(products)
+        }
+        // NT 4.0 answer files hold PIDGEN2 keys, which aren't tied to products in the dataset
+        Some(ProductKey::Pidgen2Cd { .. }) => Some(vec![PIDGEN2_CD.to_string()]),
+        Some(ProductKey::Pidgen2Oem { .. }) => Some(vec![PIDGEN2_OEM.to_string()]),
+        _ => None,
+    };
+    CheckedKey {
+        file,
+        found,
+        products,
+    }
+}
+
+/// Pulls the keys out of old answer files and reports the ones that are invalid, or aren't
+/// for the product they should be
+#[component]
+fn AnswerFileCheck(keygen: Rc<KeyTool>) -> impl IntoView {
+    let (checked, set_checked) = create_signal(Vec::<CheckedKey>::new());
+    let (checking, set_checking) = create_signal(false);
+    let (expected, set_expected) = create_signal("".to_string());
+    let (only_problems, set_only_problems) = create_signal(true);
+
+    let check_files = move |ev| {
+        set_checking.set(true);
+        spawn_local(async move {
+            let keys = files::read_all_text(&ev)
+                .await
+                .into_iter()
+                .flat_map(|(file, text)| {
+                    answer_files::find_keys(&text)
+                        .into_iter()
+                        .map(move |found| check_key(file.clone(), found))
+                })
+                .collect::<Vec<_>>();
+            set_checked.set(futures::future::join_all(keys).await);
+            set_checking.set(false);
+        });
+    };
+
+    // Every product in the dataset can be expected, not just the ones in the files checked so far
+    let families = keygen
+        .product_families()
+        .into_iter()
+        .map(|(family, products)| {
+            let names = products.iter().map(|product| product.name.clone()).collect();
+            (family.to_string(), names)
+        })
+        .chain([(
+            "PIDGEN2".to_string(),
+            vec![PIDGEN2_CD.to_string(), PIDGEN2_OEM.to_string()],
+        )])
+        .collect::<Vec<(String, Vec<String>)>>();
+
+    // What's wrong with a key, if anything
+    let problem = move |key: &CheckedKey| {
+        let expected = expected.get();
+        match &key.products {
+            None => Some("Invalid key".to_string()),
+            Some(products) if !expected.is_empty() && !products.contains(&expected) => {
+                Some(format!("Not a {expected} key"))
+            }
+            Some(_) => None,
+        }
+    };
+
+    let summary = move || {
+        let checked = checked.get();
+        let files = checked.iter().map(|key| &key.file).collect::<BTreeSet<_>>().len();
+        let problems = checked.iter().filter(|key| problem(key).is_some()).count();
+        format!(
+            "Found {} keys in {files} files, {problems} with problems",
+            checked.len()
+        )
+    };
+
+    let rows = move || {
+        checked
+            .get()
+            .into_iter()
+            .filter_map(|key| {
+                let problem = problem(&key);
+                (problem.is_some() || !only_problems.get()).then_some((key, problem))
+            })
+            .enumerate()
+            .map(|(i, (key, problem))| {
+                let class = if i % 2 == 0 { "" } else { "bg-slate-100 dark:bg-slate-700" };
+                let result = match problem {
+                    Some(problem) => {
+                        view! { <span class="text-rose-600 dark:text-rose-400">{problem}</span> }
+                    }
+                    None => view! { <span>{key.products.unwrap_or_default().join(", ")}</span> },
+                };
+                view! {
+                    <tr class=class>
+                        <td class="px-4 py-2 break-all">{format!("{}:{}", key.file, key.found.line)}</td>
+                        <td class="px-4 py-2">{key.found.field}</td>
+                        <td class="font-mono px-4 py-2 break-all">{key.found.key}</td>
+                        <td class="px-4 py-2">{result}</td>
+                    </tr>
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <p class="mb-4">
+            "Pick winnt.sif, unattend.txt, sysprep.inf or setup.ini files to check the ProductKey, ProductID and PIDKEY entries in them."
+        </p>
+        <div class="flex flex-col sm:flex-row sm:items-end gap-4 mb-6">
+            <div class="flex-1">
+                <label class="block text-center shadow-sm rounded-lg px-4 py-2 cursor-pointer text-slate-100 bg-indigo-500 hover:bg-indigo-600 dark:bg-indigo-600 dark:hover:bg-indigo-700">
+                    "Choose Files"
+                    <input
+                        type="file"
+                        multiple
+                        accept=".sif,.txt,.inf,.ini"
+                        class="hidden"
+                        on:change=check_files
+                    />
+                </label>
+            </div>
+            <div class="flex-[2]">
+                <SelectField
+                    label="Expected Product"
+                    id="answerexpected"
+                    on_input=move |ev| set_expected.set(event_target_value(&ev))
+                >
+                    <option value="">"Any"</option>
+                    {move || {
+                        let selected = expected.get();
+                        families
+                            .iter()
+                            .map(|(family, names)| {
+                                let options = names
+                                    .iter()
+                                    .map(|name| {
+                                        view! {
+                                            <option value=name.clone() selected=*name == selected>
+                                                {name.clone()}
+                                            </option>
+                                        }
+                                    })
+                                    .collect::<Vec<_>>();
+                                view! { <optgroup label=family.clone()>{options}</optgroup> }
+                            })
+                            .collect::<Vec<_>>()
+                    }}
+                </SelectField>
+            </div>
+            <div class="flex-1">
+                <CheckBox
+                    label="Only Problems"
+                    id="answeronlyproblems"
+                    checked=only_problems
+                    on_change=move |ev| set_only_problems.set(event_target_checked(&ev))
+                />
             </div>
         </div>
+        <Show when=move || checking.get()>
+            <Loading />
+        </Show>
+        <Show when=move || !checking.get() && !checked.with(Vec::is_empty)>
+            <p class="mb-4">{summary}</p>
+            <div class="overflow-x-auto">
+                <table class="w-full text-left">
+                    <thead>
+                        <tr>
+                            <th class="font-semibold px-4 py-2">"File:Line"</th>
+                            <th class="font-semibold px-4 py-2">"Field"</th>
+                            <th class="font-semibold px-4 py-2">"Key"</th>
+                            <th class="font-semibold px-4 py-2">"Result"</th>
+                        </tr>
+                    </thead>
+                    <tbody>{rows}</tbody>
+                </table>
+            </div>
+        </Show>
     }
 }
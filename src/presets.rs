@@ -0,0 +1,264 @@
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::KeyTool;
+
+pub const STORAGE_KEY: &str = "mskey-web.presets";
+
+/// Whether a preset generates keys with a random sequence or always the same one
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SequencePolicy {
+    Random,
+    Fixed(u32),
+}
+
+impl SequencePolicy {
+    /// Reads the policy from the Sequence field, where empty means random
+    pub fn from_field(sequence: &str) -> Self {
+        match sequence.trim().parse() {
+            Ok(sequence) => SequencePolicy::Fixed(sequence),
+            Err(_) => SequencePolicy::Random,
+        }
+    }
+
+    pub fn to_field(&self) -> String {
+        match self {
+            SequencePolicy::Random => String::new(),
+            SequencePolicy::Fixed(sequence) => sequence.to_string(),
+        }
+    }
+}
+
+/// A named set of Generate parameters. These are shared between people as JSON files, so BINK
+/// IDs are kept as hex strings like they are in keys.json.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub product: String,
+    pub bink: String,
+    pub channel: String,
+    pub sequence: SequencePolicy,
+    #[serde(default)]
+    pub upgrade: bool,
+}
+
+impl Preset {
+    pub fn bink_id(&self) -> Result<u8> {
+        u8::from_str_radix(self.bink.trim(), 16)
+            .map_err(|_| anyhow!("{}: BINK ID {} is not a hex byte", self.name, self.bink))
+    }
+
+    /// Checks that the preset can be used with this build's dataset
+    pub fn check(&self, keygen: &KeyTool) -> Result<()> {
+        if self.name.trim().is_empty() {
+            bail!("Presets need a name");
+        }
+        let Some(product) = keygen
+            .sorted_products()
+            .iter()
+            .find(|product| product.name == self.product)
+        else {
+            bail!("{}: unknown product {}", self.name, self.product);
+        };
+        let bink_id = self.bink_id()?;
+        if !product.bink_ids.contains(&bink_id) {
+            bail!("{}: {} has no BINK {bink_id:02X}", self.name, self.product);
+        }
+        if !self
+            .channel
+            .parse::<u16>()
+            .is_ok_and(|channel| channel <= 999)
+        {
+            bail!("{}: channel ID {} is not 3 digits", self.name, self.channel);
+        }
+        if let SequencePolicy::Fixed(sequence) = self.sequence {
+            if sequence > 999999 {
+                bail!("{}: sequence {sequence} is more than 6 digits", self.name);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Adds presets to a list, replacing any with the same name, and keeps it sorted
+pub fn merge(presets: &mut Vec<Preset>, new: impl IntoIterator<Item = Preset>) {
+    for preset in new {
+        presets.retain(|existing| existing.name != preset.name);
+        presets.push(preset);
+    }
+    presets.sort_by(|a, b| a.name.cmp(&b.name));
+}
+
+/// Parses an exported presets file, rejecting it if any preset doesn't fit the dataset
+pub fn import(json: &str, keygen: &KeyTool) -> Result<Vec<Preset>> {
+    let presets: Vec<Preset> =
+        serde_json::from_str(json).map_err(|err| anyhow!("Not a presets file: {err}"))?;
+    for preset in &presets {
+        preset.check(keygen)?;
+    }
+    Ok(presets)
+}
+
+pub fn export(presets: &[Preset]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(presets)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::key_tool;
+
+    fn preset(name: &str) -> Preset {
+        Preset {
+            name: name.to_string(),
+            product: "Test 1998".to_string(),
+            bink: "01".to_string(),
+            channel: "640".to_string(),
+            sequence: SequencePolicy::Random,
+            upgrade: false,
+        }
+    }
+
+    fn check_error(preset: Preset) -> String {
+        preset.check(&key_tool()).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn reads_sequence_fields() {
+        assert_eq!(SequencePolicy::from_field(""), SequencePolicy::Random);
+        assert_eq!(SequencePolicy::from_field("  "), SequencePolicy::Random);
+        assert_eq!(SequencePolicy::from_field("abc"), SequencePolicy::Random);
+        assert_eq!(
+            SequencePolicy::from_field(" 42 "),
+            SequencePolicy::Fixed(42)
+        );
+        assert_eq!(SequencePolicy::from_field("0"), SequencePolicy::Fixed(0));
+        for policy in [SequencePolicy::Random, SequencePolicy::Fixed(123456)] {
+            assert_eq!(SequencePolicy::from_field(&policy.to_field()), policy);
+        }
+    }
+
+    #[test]
+    fn accepts_presets_that_fit_the_dataset() {
+        let key_tool = key_tool();
+        assert!(preset("Retail").check(&key_tool).is_ok());
+        let preset = Preset {
+            product: "Test 2002".to_string(),
+            bink: "41".to_string(),
+            channel: "0".to_string(),
+            sequence: SequencePolicy::Fixed(999999),
+            upgrade: true,
+            ..preset("Upgrade")
+        };
+        assert!(preset.check(&key_tool).is_ok());
+    }
+
+    #[test]
+    fn rejects_presets_that_dont_fit_the_dataset() {
+        assert_eq!(check_error(preset(" ")), "Presets need a name");
+        assert_eq!(
+            check_error(Preset {
+                product: "Windows 95".to_string(),
+                ..preset("A")
+            }),
+            "A: unknown product Windows 95"
+        );
+        assert_eq!(
+            check_error(Preset {
+                bink: "41".to_string(),
+                ..preset("A")
+            }),
+            "A: Test 1998 has no BINK 41"
+        );
+        assert_eq!(
+            check_error(Preset {
+                bink: "zz".to_string(),
+                ..preset("A")
+            }),
+            "A: BINK ID zz is not a hex byte"
+        );
+        for channel in ["1000", "-1", "", "abc"] {
+            assert_eq!(
+                check_error(Preset {
+                    channel: channel.to_string(),
+                    ..preset("A")
+                }),
+                format!("A: channel ID {channel} is not 3 digits")
+            );
+        }
+        assert_eq!(
+            check_error(Preset {
+                sequence: SequencePolicy::Fixed(1_000_000),
+                ..preset("A")
+            }),
+            "A: sequence 1000000 is more than 6 digits"
+        );
+    }
+
+    #[test]
+    fn merging_replaces_presets_by_name() {
+        let mut presets = vec![preset("B"), preset("A")];
+        let replacement = Preset {
+            channel: "270".to_string(),
+            ..preset("B")
+        };
+        merge(&mut presets, [replacement.clone(), preset("C")]);
+        assert_eq!(presets, [preset("A"), replacement, preset("C")]);
+    }
+
+    #[test]
+    fn exported_presets_import_as_they_were() {
+        let presets = vec![
+            preset("Random"),
+            Preset {
+                product: "Test 2002".to_string(),
+                bink: "41".to_string(),
+                sequence: SequencePolicy::Fixed(42),
+                upgrade: true,
+                ..preset("Fixed")
+            },
+        ];
+        let json = export(&presets).unwrap();
+        assert_eq!(import(&json, &key_tool()).unwrap(), presets);
+    }
+
+    #[test]
+    fn imports_only_presets_that_fit_the_dataset() {
+        let key_tool = key_tool();
+        assert!(import("{}", &key_tool)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Not a presets file"));
+
+        // The upgrade field can be left out
+        let json = r#"[{
+            "name": "Old",
+            "product": "Test 1998",
+            "bink": "01",
+            "channel": "640",
+            "sequence": {"fixed": 7}
+        }]"#;
+        assert_eq!(
+            import(json, &key_tool).unwrap(),
+            [Preset {
+                sequence: SequencePolicy::Fixed(7),
+                ..preset("Old")
+            }]
+        );
+
+        // One bad preset rejects the whole file
+        let json = export(&[
+            preset("Good"),
+            Preset {
+                bink: "41".to_string(),
+                ..preset("Bad")
+            },
+        ])
+        .unwrap();
+        assert_eq!(
+            import(&json, &key_tool).unwrap_err().to_string(),
+            "Bad: Test 1998 has no BINK 41"
+        );
+    }
+}
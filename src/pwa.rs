@@ -0,0 +1,77 @@
+use leptos::*;
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{ServiceWorkerRegistration, ServiceWorkerState};
+
+/// Registers the service worker that caches the app for offline use. The returned signal turns
+/// true once a newer version has been cached and is waiting to take over.
+pub fn register() -> ReadSignal<bool> {
+    let (update_ready, set_update_ready) = create_signal(false);
+    // A cached app would get in the way of `trunk serve` reloading debug builds
+    if cfg!(debug_assertions) {
+        return update_ready;
+    }
+
+    spawn_local(async move {
+        let container = window().navigator().service_worker();
+        let Ok(registration) = JsFuture::from(container.register("/sw.js")).await else {
+            return;
+        };
+        let registration = registration.unchecked_into::<ServiceWorkerRegistration>();
+
+        // The update may have been cached on an earlier visit
+        if registration.waiting().is_some() && container.controller().is_some() {
+            set_update_ready.set(true);
+        }
+
+        let update_registration = registration.clone();
+        let on_update_found = Closure::<dyn Fn()>::new(move || {
+            let Some(installing) = update_registration.installing() else {
+                return;
+            };
+            let worker = installing.clone();
+            let on_state_change = Closure::<dyn Fn()>::new(move || {
+                // The very first install only caches the version that's already running
+                let has_controller = window().navigator().service_worker().controller().is_some();
+                if worker.state() == ServiceWorkerState::Installed && has_controller {
+                    set_update_ready.set(true);
+                }
+            });
+            installing.set_onstatechange(Some(on_state_change.as_ref().unchecked_ref()));
+            on_state_change.forget();
+        });
+        registration.set_onupdatefound(Some(on_update_found.as_ref().unchecked_ref()));
+        on_update_found.forget();
+    });
+
+    update_ready
+}
+
+/// Hands over to the waiting version of the app and reloads into it
+pub fn apply_update() {
+    let container = window().navigator().service_worker();
+    let on_controller_change = Closure::once_into_js(|| {
+        let _ = window().location().reload();
+    });
+    container.set_oncontrollerchange(Some(on_controller_change.unchecked_ref()));
+
+    spawn_local(async move {
+        let waiting = JsFuture::from(container.get_registration())
+            .await
+            .ok()
+            .and_then(|registration| {
+                registration
+                    .dyn_into::<ServiceWorkerRegistration>()
+                    .ok()?
+                    .waiting()
+            });
+        match waiting {
+            Some(waiting) => {
+                let _ = waiting.post_message(&JsValue::from_str("skipWaiting"));
+            }
+            None => {
+                let _ = window().location().reload();
+            }
+        }
+    });
+}
@@ -0,0 +1,148 @@
+use leptos::*;
+use leptos_router::NavigateOptions;
+use wasm_bindgen::JsValue;
+use web_sys::UrlSearchParams;
+
+use crate::components::error::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Generate,
+    Validate,
+    Activate,
+    Custom,
+    Dataset,
+}
+
+impl Tab {
+    pub fn class(tab: Tab, selected: Option<Tab>) -> String {
+        let class = if Some(tab) == selected {
+            "inline-block w-full p-3 text-slate-900 bg-slate-200 active focus:outline-none dark:bg-slate-700 dark:text-white"
+        } else {
+            "inline-block w-full p-3 bg-slate-50 hover:text-slate-700 hover:bg-slate-100 focus:outline-none dark:hover:text-white dark:bg-slate-900 dark:hover:bg-slate-700"
+        };
+        match tab {
+            Tab::Generate => format!("{} rounded-t-lg sm:rounded-none sm:rounded-s-lg", class),
+            Tab::Validate | Tab::Activate | Tab::Custom => class.to_string(),
+            Tab::Dataset => format!("{} rounded-b-lg sm:rounded-none sm:rounded-e-lg", class),
+        }
+    }
+
+    pub fn path(self) -> String {
+        format!("/{self}")
+    }
+
+    /// The tab whose route the path falls under, e.g. `/validate/KEY` is under Validate
+    pub fn from_path(path: &str) -> Option<Tab> {
+        match path.trim_start_matches('/').split('/').next()? {
+            "generate" => Some(Tab::Generate),
+            "validate" => Some(Tab::Validate),
+            "activate" => Some(Tab::Activate),
+            "custom" => Some(Tab::Custom),
+            "dataset" => Some(Tab::Dataset),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Tab {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tab::Generate => write!(f, "generate"),
+            Tab::Validate => write!(f, "validate"),
+            Tab::Activate => write!(f, "activate"),
+            Tab::Custom => write!(f, "custom"),
+            Tab::Dataset => write!(f, "dataset"),
+        }
+    }
+}
+
+/// Navigation options for redirects and URL updates that shouldn't add a history entry
+pub fn replace() -> NavigateOptions {
+    NavigateOptions {
+        replace: true,
+        scroll: false,
+        ..Default::default()
+    }
+}
+
+/// Updates the address bar to reflect the current form state without adding a history entry.
+///
+/// This goes around the router on purpose, so the page isn't re-rendered while it's being typed
+/// into. Going back or reloading then routes to the page with this state restored.
+pub fn replace_url(path: &str, query: &[(&str, &str)]) {
+    let search = UrlSearchParams::new().unwrap();
+    for (name, value) in query {
+        if !value.is_empty() {
+            search.append(name, value);
+        }
+    }
+    let search = String::from(search.to_string());
+    let url = if search.is_empty() {
+        path.to_string()
+    } else {
+        format!("{path}?{search}")
+    };
+    let _ = web_sys::window()
+        .unwrap()
+        .history()
+        .unwrap()
+        .replace_state_with_url(&JsValue::NULL, "", Some(&url));
+}
+
+/// Like [`replace_url`], but only while the address bar is still on the tab. Pages that update
+/// the URL once a worker replies use this, since another tab may have been opened by then.
+pub fn replace_url_on(tab: Tab, path: &str, query: &[(&str, &str)]) {
+    let current = web_sys::window()
+        .unwrap()
+        .location()
+        .pathname()
+        .unwrap_or_default();
+    if Tab::from_path(&current) == Some(tab) {
+        replace_url(path, query);
+    }
+}
+
+/// The Validate path for a key, which is encoded so that whatever was typed stays one segment
+pub fn validate_path(key: &str) -> String {
+    format!(
+        "{}/{}",
+        Tab::Validate.path(),
+        js_sys::encode_uri_component(key.trim())
+    )
+}
+
+/// Reads a query parameter straight from the address bar
+pub fn query_param(name: &str) -> Option<String> {
+    let search = web_sys::window().unwrap().location().search().ok()?;
+    UrlSearchParams::new_with_str(&search).ok()?.get(name)
+}
+
+/// Where links from before there were routes, such as `?validate&k=KEY`, now go
+pub fn legacy_path() -> Option<String> {
+    let search = web_sys::window().unwrap().location().search().unwrap();
+    let search = UrlSearchParams::new_with_str(&search).unwrap();
+
+    if search.get("dataset").is_some() {
+        Some(Tab::Dataset.path())
+    } else if search.get("custom").is_some() {
+        Some(Tab::Custom.path())
+    } else if search.get("activate").is_some() {
+        Some(Tab::Activate.path())
+    } else if search.get("validate").is_some() {
+        match search.get("k") {
+            Some(key) if !key.trim().is_empty() => Some(validate_path(&key)),
+            _ => Some(Tab::Validate.path()),
+        }
+    } else if search.get("generate").is_some() {
+        Some(Tab::Generate.path())
+    } else {
+        None
+    }
+}
+
+#[component]
+pub fn NotFound() -> impl IntoView {
+    let path = web_sys::window().unwrap().location().pathname().unwrap();
+    view! { <Error>{format!("There is no page at {path}")}</Error> }
+}
@@ -0,0 +1,259 @@
+use crate::crypto::KEY_CHARS;
+
+/// Digits in the installation IDs that confirmation IDs can be worked out for, counting the
+/// check digit at the end of each group
+const INSTALLATION_ID_LENGTHS: [usize; 2] = [50, 54];
+
+/// How much of the line to show either side of a hit
+const CONTEXT: usize = 32;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Found {
+    /// With dashes, whether or not the text had them
+    Key(String),
+    /// Just the digits
+    InstallationId(String),
+}
+
+/// Something that looks like a key or an installation ID, and where it was
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hit {
+    /// Counting from 1
+    pub line: usize,
+    pub before: String,
+    pub text: String,
+    pub after: String,
+    pub found: Found,
+}
+
+/// Finds everything shaped like a product key or installation ID
+pub fn scan(text: &str) -> Vec<Hit> {
+    text.lines()
+        .enumerate()
+        .flat_map(|(i, line)| {
+            let chars = line.chars().collect::<Vec<_>>();
+            let mut hits = find_keys(&chars)
+                .chain(find_installation_ids(&chars))
+                .collect::<Vec<_>>();
+            hits.sort_by_key(|(start, _, _)| *start);
+            hits.into_iter()
+                .map(|(start, end, found)| Hit {
+                    line: i + 1,
+                    before: context(&chars[..start], true),
+                    text: chars[start..end].iter().collect(),
+                    after: context(&chars[end..], false),
+                    found,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Up to `CONTEXT` characters of the rest of the line next to a hit, marking where it's been cut
+fn context(chars: &[char], before: bool) -> String {
+    if chars.len() <= CONTEXT {
+        return chars.iter().collect();
+    }
+    if before {
+        let text = chars[chars.len() - CONTEXT..].iter().collect::<String>();
+        format!("…{}", text.trim_start())
+    } else {
+        let text = chars[..CONTEXT].iter().collect::<String>();
+        format!("{}…", text.trim_end())
+    }
+}
+
+fn is_key_char(c: char) -> bool {
+    u8::try_from(c.to_ascii_uppercase()).is_ok_and(|c| KEY_CHARS.contains(&c))
+}
+
+/// Runs of letters and digits that are five groups of five, with or without dashes between
+fn find_keys(chars: &[char]) -> impl Iterator<Item = (usize, usize, Found)> + '_ {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        while start < chars.len() {
+            // Only look where a word starts
+            if !chars[start].is_ascii_alphanumeric()
+                || (start > 0 && chars[start - 1].is_ascii_alphanumeric())
+            {
+                start += 1;
+                continue;
+            }
+            let mut run = chars[start..]
+                .iter()
+                .take_while(|c| c.is_ascii_alphanumeric() || **c == '-')
+                .count();
+            while chars[start + run - 1] == '-' {
+                run -= 1;
+            }
+            let word = &chars[start..start + run];
+            let found_at = start;
+            start += run.max(1);
+
+            let groups = word.split(|c| *c == '-').collect::<Vec<_>>();
+            let dashed = groups.len() == 5 && groups.iter().all(|group| group.len() == 5);
+            let plain = word.len() == 25 && word.iter().all(|c| is_key_char(*c));
+            if dashed || plain {
+                let key = word
+                    .iter()
+                    .filter(|c| **c != '-')
+                    .map(|c| c.to_ascii_uppercase())
+                    .collect::<Vec<_>>()
+                    .chunks(5)
+                    .map(|group| group.iter().collect::<String>())
+                    .collect::<Vec<_>>()
+                    .join("-");
+                return Some((found_at, found_at + run, Found::Key(key)));
+            }
+        }
+        None
+    })
+}
+
+/// Runs of digits, which may be split into groups by single dashes or spaces, that are as long
+/// as an installation ID
+fn find_installation_ids(chars: &[char]) -> impl Iterator<Item = (usize, usize, Found)> + '_ {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        while start < chars.len() {
+            if !chars[start].is_ascii_digit()
+                || (start > 0 && chars[start - 1].is_ascii_alphanumeric())
+            {
+                start += 1;
+                continue;
+            }
+            let mut end = start;
+            while end < chars.len() {
+                if chars[end].is_ascii_digit() {
+                    end += 1;
+                } else if matches!(chars[end], '-' | ' ')
+                    && chars.get(end + 1).is_some_and(char::is_ascii_digit)
+                {
+                    end += 2;
+                } else {
+                    break;
+                }
+            }
+            let found_at = start;
+            start = end;
+
+            if chars.get(end).is_some_and(char::is_ascii_alphanumeric) {
+                continue;
+            }
+            let digits = chars[found_at..end]
+                .iter()
+                .filter(|c| c.is_ascii_digit())
+                .collect::<String>();
+            if INSTALLATION_ID_LENGTHS.contains(&digits.len()) {
+                return Some((found_at, end, Found::InstallationId(digits)));
+            }
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "FCKGW-RHQQ2-YXRKT-8TG6W-2B7Q8";
+    const IID_54: &str = "334481-558826-870862-843844-566221-823392-794862-457401-103810";
+    const IID_50: &str = "12345678901234567890123456789012345678901234567890";
+
+    fn found(text: &str) -> Vec<Found> {
+        scan(text).into_iter().map(|hit| hit.found).collect()
+    }
+
+    fn key() -> Found {
+        Found::Key(KEY.to_string())
+    }
+
+    #[test]
+    fn finds_keys_with_and_without_dashes() {
+        assert_eq!(found(KEY), [key()]);
+        assert_eq!(found(&KEY.replace('-', "")), [key()]);
+        assert_eq!(found(&KEY.to_lowercase()), [key()]);
+        assert_eq!(
+            found(&format!("Key: {KEY}, then {}.", KEY.replace('-', ""))),
+            [key(), key()]
+        );
+    }
+
+    #[test]
+    fn skips_keys_inside_words() {
+        assert!(found(&format!("x{KEY}")).is_empty());
+        assert!(found(&format!("{KEY}x")).is_empty());
+        assert!(found(&format!("{KEY}-BBBBB")).is_empty());
+        assert!(found(&format!("x{}", KEY.replace('-', ""))).is_empty());
+        // 25 characters, but not all of them can be in a key
+        assert!(found("AAAAAAAAAAAAAAAAAAAAAAAAA").is_empty());
+        assert!(found("FCKGW-RHQQ2-YXRKT-8TG6W-2B7Q").is_empty());
+        // A trailing dash isn't part of the key
+        assert_eq!(found(&format!("{KEY}-")), [key()]);
+    }
+
+    #[test]
+    fn finds_installation_ids() {
+        let digits_54 = IID_54.replace('-', "");
+        for text in [
+            IID_54.to_string(),
+            IID_54.replace('-', " "),
+            digits_54.clone(),
+        ] {
+            assert_eq!(
+                found(&text),
+                [Found::InstallationId(digits_54.clone())],
+                "{text}"
+            );
+        }
+        assert_eq!(
+            found(&format!("IID {IID_50}.")),
+            [Found::InstallationId(IID_50.to_string())]
+        );
+    }
+
+    #[test]
+    fn skips_numbers_that_arent_installation_ids() {
+        // Too short, too long, run into a word, and split by more than a single separator
+        assert!(found(&IID_50[1..]).is_empty());
+        assert!(found(&format!("{IID_54}1")).is_empty());
+        assert!(found(&format!("x{IID_50}")).is_empty());
+        assert!(found(&format!("{IID_50}x")).is_empty());
+        assert!(found(&IID_54.replace('-', "--")).is_empty());
+    }
+
+    #[test]
+    fn reports_lines_and_order() {
+        let text = format!("nothing here\n{IID_50} {KEY}\n\n{KEY}");
+        let hits = scan(&text);
+        assert_eq!(
+            hits.iter()
+                .map(|hit| (hit.line, hit.found.clone()))
+                .collect::<Vec<_>>(),
+            [
+                (2, Found::InstallationId(IID_50.to_string())),
+                (2, key()),
+                (4, key()),
+            ]
+        );
+        assert_eq!(hits[1].text, KEY);
+        assert_eq!(
+            hits[1].before,
+            format!("…{} ", &IID_50[50 - (CONTEXT - 1)..])
+        );
+        assert_eq!(hits[1].after, "");
+    }
+
+    #[test]
+    fn context_only_marks_cut_text() {
+        let side = "a".repeat(CONTEXT - 1);
+        // Exactly CONTEXT characters either side, so nothing is cut
+        let hit = &scan(&format!("{side} {KEY} {side}"))[0];
+        assert_eq!(hit.before, format!("{side} "));
+        assert_eq!(hit.after, format!(" {side}"));
+
+        let hit = &scan(&format!("b{side} {KEY} {side}b"))[0];
+        assert_eq!(hit.before, format!("…{side} "));
+        assert_eq!(hit.after, format!(" {side}…"));
+    }
+}
@@ -0,0 +1,172 @@
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+pub const STORAGE_KEY: &str = "mskey-web.settings";
+
+/// How numbers are shown in the key details
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NumberFormat {
+    #[default]
+    Both,
+    Decimal,
+    Hex,
+}
+
+impl NumberFormat {
+    pub const ALL: [NumberFormat; 3] =
+        [NumberFormat::Both, NumberFormat::Decimal, NumberFormat::Hex];
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.id() == id)
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            NumberFormat::Both => "both",
+            NumberFormat::Decimal => "decimal",
+            NumberFormat::Hex => "hex",
+        }
+    }
+}
+
+impl std::fmt::Display for NumberFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumberFormat::Both => write!(f, "Decimal and hex"),
+            NumberFormat::Decimal => write!(f, "Decimal"),
+            NumberFormat::Hex => write!(f, "Hex"),
+        }
+    }
+}
+
+/// How big QR codes are drawn
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QrSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+}
+
+impl QrSize {
+    pub const ALL: [QrSize; 3] = [QrSize::Small, QrSize::Medium, QrSize::Large];
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|size| size.id() == id)
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            QrSize::Small => "small",
+            QrSize::Medium => "medium",
+            QrSize::Large => "large",
+        }
+    }
+
+    pub fn pixels(self) -> u32 {
+        match self {
+            QrSize::Small => 128,
+            QrSize::Medium => 192,
+            QrSize::Large => 288,
+        }
+    }
+}
+
+impl std::fmt::Display for QrSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QrSize::Small => write!(f, "Small"),
+            QrSize::Medium => write!(f, "Medium"),
+            QrSize::Large => write!(f, "Large"),
+        }
+    }
+}
+
+/// How much of a QR code can be damaged or obscured and still scan, at the cost of density
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QrErrorCorrection {
+    Low,
+    #[default]
+    Medium,
+    Quartile,
+    High,
+}
+
+impl QrErrorCorrection {
+    pub const ALL: [QrErrorCorrection; 4] = [
+        QrErrorCorrection::Low,
+        QrErrorCorrection::Medium,
+        QrErrorCorrection::Quartile,
+        QrErrorCorrection::High,
+    ];
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|level| level.id() == id)
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            QrErrorCorrection::Low => "L",
+            QrErrorCorrection::Medium => "M",
+            QrErrorCorrection::Quartile => "Q",
+            QrErrorCorrection::High => "H",
+        }
+    }
+}
+
+impl std::fmt::Display for QrErrorCorrection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QrErrorCorrection::Low => write!(f, "Low (7%)"),
+            QrErrorCorrection::Medium => write!(f, "Medium (15%)"),
+            QrErrorCorrection::Quartile => write!(f, "Quartile (25%)"),
+            QrErrorCorrection::High => write!(f, "High (30%)"),
+        }
+    }
+}
+
+/// Display preferences, kept in localStorage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Show keys grouped with dashes rather than as one run of characters
+    pub dashed_keys: bool,
+    pub number_format: NumberFormat,
+    pub qr_size: QrSize,
+    pub qr_error_correction: QrErrorCorrection,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            dashed_keys: true,
+            number_format: NumberFormat::default(),
+            qr_size: QrSize::default(),
+            qr_error_correction: QrErrorCorrection::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Formats a key the way the user prefers to see it
+    pub fn format_key(&self, key: &str) -> String {
+        if self.dashed_keys {
+            key.to_string()
+        } else {
+            key.replace('-', "")
+        }
+    }
+}
+
+/// Loads the settings and makes them available to every component below, saving any changes
+pub fn provide_settings() {
+    let settings = create_rw_signal(storage::load::<Settings>(STORAGE_KEY).unwrap_or_default());
+    create_effect(move |_| settings.with(|settings| storage::save(STORAGE_KEY, settings)));
+    provide_context(settings);
+}
+
+pub fn use_settings() -> RwSignal<Settings> {
+    expect_context::<RwSignal<Settings>>()
+}
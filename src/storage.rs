@@ -0,0 +1,25 @@
+use serde::{de::DeserializeOwned, Serialize};
+use web_sys::Storage;
+
+fn local_storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Reads a JSON value from localStorage, returning `None` if it's missing or malformed
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let value = local_storage()?.get_item(key).ok()??;
+    serde_json::from_str(&value).ok()
+}
+
+/// Writes a value to localStorage as JSON
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    if let (Some(storage), Ok(value)) = (local_storage(), serde_json::to_string(value)) {
+        let _ = storage.set_item(key, &value);
+    }
+}
+
+pub fn remove(key: &str) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.remove_item(key);
+    }
+}
@@ -0,0 +1,23 @@
+//! Helpers shared between the tests of different modules
+
+use bink_types::{bink, channels::Channels, keys};
+
+use crate::crypto::KeyTool;
+
+/// The toy curves in `testdata/keys.json`
+pub fn test_keys() -> bink::Keys {
+    let keys: keys::Keys = serde_json::from_str(include_str!("../testdata/keys.json")).unwrap();
+    bink::Keys::from_json(&keys, Channels::default())
+}
+
+/// A `KeyTool` for the test dataset, whose products are "Test 1998" with BINK 01 and
+/// "Test 2002" with BINK 41
+pub fn key_tool() -> KeyTool {
+    KeyTool::from_keys(test_keys())
+}
+
+/// A source of "random" numbers that goes round the given values
+pub fn cycle(values: Vec<f64>) -> impl FnMut() -> f64 {
+    let mut values = values.into_iter().cycle();
+    move || values.next().unwrap()
+}
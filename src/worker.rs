@@ -0,0 +1,187 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use futures::channel::oneshot;
+use leptos::spawn_local;
+use leptos_workers::{worker, Receiver, Sender};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{KeyTool, ProductKey};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenKeyRequest {
+    pub bink_id: u8,
+    pub channel_id: String,
+    pub sequence: String,
+    /// Only used by BINK2002, and random when blank
+    #[serde(default)]
+    pub auth_info: String,
+    pub upgrade: bool,
+}
+
+/// The operations a key worker can perform
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Request {
+    Validate(String),
+    Generate(GenKeyRequest),
+    ConfirmationId(String),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Response {
+    Validate(ProductKey),
+    /// The key, or why it couldn't be generated
+    Generate(Result<String, String>),
+    /// The confirmation ID, or why the installation ID was rejected
+    ConfirmationId(Result<String, String>),
+}
+
+/// A long-lived worker that loads the dataset once and then serves requests until its channel
+/// is dropped
+#[worker]
+async fn key_worker(rx: Receiver<(u64, Request)>, tx: Sender<(u64, Response)>) {
+    let key_tool = KeyTool::new();
+    while let Ok((id, request)) = rx.recv_async().await {
+        let response = match request {
+            Request::Validate(key) => {
+                Response::Validate(key_tool.validate_key(&key).unwrap_or(ProductKey::Invalid))
+            }
+            Request::Generate(request) => Response::Generate(
+                key_tool
+                    .gen_key(
+                        request.bink_id,
+                        &request.channel_id,
+                        &request.sequence,
+                        &request.auth_info,
+                        request.upgrade,
+                    )
+                    .map_err(|err| err.to_string()),
+            ),
+            Request::ConfirmationId(installation_id) => {
+                Response::ConfirmationId(
+                    KeyTool::get_confirmation_id(&installation_id).map_err(|err| err.to_string()),
+                )
+            }
+        };
+        if tx.send((id, response)).is_err() {
+            break;
+        }
+    }
+}
+
+type Pending = Rc<RefCell<HashMap<u64, oneshot::Sender<Response>>>>;
+
+struct PoolWorker {
+    tx: Sender<(u64, Request)>,
+    pending: Pending,
+    /// Set once the worker has stopped responding, so no more requests are sent to it
+    closed: Rc<Cell<bool>>,
+}
+
+/// Spreads requests over one key worker per logical core, from single lookups to the items of
+/// bulk jobs
+pub struct WorkerPool {
+    workers: Vec<PoolWorker>,
+    next_id: Cell<u64>,
+}
+
+impl WorkerPool {
+    /// Starts the workers, or says why they couldn't be started, for example when the browser
+    /// doesn't allow workers at all
+    fn new(size: usize) -> Result<Self, String> {
+        let workers = (0..size)
+            .map(|_| {
+                let (tx, rx) =
+                    key_worker().map_err(|err| format!("Couldn't start a key worker: {err:?}"))?;
+                let pending: Pending = Rc::default();
+                let closed = Rc::new(Cell::new(false));
+                let responses = pending.clone();
+                let worker_closed = closed.clone();
+                spawn_local(async move {
+                    while let Ok((id, response)) = rx.recv_async().await {
+                        if let Some(sender) = responses.borrow_mut().remove(&id) {
+                            let _ = sender.send(response);
+                        }
+                    }
+                    // The worker has died, so nothing it was sent will be answered. Dropping the
+                    // senders lets the requests waiting on them give up.
+                    worker_closed.set(true);
+                    responses.borrow_mut().clear();
+                });
+                Ok(PoolWorker {
+                    tx,
+                    pending,
+                    closed,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self {
+            workers,
+            next_id: Cell::new(0),
+        })
+    }
+
+    /// How many workers there are
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Sends a request to the least busy worker and waits for its response, or `None` if no
+    /// worker is left to answer it
+    pub async fn request(&self, request: Request) -> Option<Response> {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+
+        let worker = self
+            .workers
+            .iter()
+            .filter(|worker| !worker.closed.get())
+            .min_by_key(|worker| worker.pending.borrow().len())?;
+        let (sender, receiver) = oneshot::channel();
+        worker.pending.borrow_mut().insert(id, sender);
+        if worker.tx.send((id, request)).is_err() {
+            worker.pending.borrow_mut().remove(&id);
+            return None;
+        }
+        receiver.await.ok()
+    }
+}
+
+thread_local! {
+    static POOL: Result<Rc<WorkerPool>, String> = {
+        let cores = web_sys::window()
+            .map(|window| window.navigator().hardware_concurrency() as usize)
+            .unwrap_or(1);
+        WorkerPool::new(cores.max(1)).map(Rc::new)
+    };
+}
+
+/// Returns the shared worker pool, starting the workers on first use, or why they couldn't be
+/// started
+pub fn pool() -> Result<Rc<WorkerPool>, String> {
+    POOL.with(|pool| pool.clone())
+}
+
+pub async fn validate_key(key: String) -> Option<ProductKey> {
+    match pool().ok()?.request(Request::Validate(key)).await {
+        Some(Response::Validate(product_key)) => Some(product_key),
+        _ => None,
+    }
+}
+
+pub async fn gen_key(request: GenKeyRequest) -> Result<String, String> {
+    match pool()?.request(Request::Generate(request)).await {
+        Some(Response::Generate(key)) => key,
+        _ => Err("The key worker didn't respond".to_string()),
+    }
+}
+
+pub async fn confirmation_id(installation_id: String) -> Option<String> {
+    match pool().ok()?.request(Request::ConfirmationId(installation_id)).await {
+        Some(Response::ConfirmationId(confirmation_id)) => confirmation_id.ok(),
+        _ => None,
+    }
+}
@@ -0,0 +1,70 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    env, error, fs,
+    hash::Hasher,
+    path::{Path, PathBuf},
+};
+
+const TEMPLATE: &str = include_str!("sw.js");
+const OUTPUT: &str = "sw.js";
+
+/// Writes the service worker into Trunk's staging directory, listing every file of the build to
+/// be cached. Run as a Trunk post_build hook.
+fn main() -> Result<(), Box<dyn error::Error>> {
+    let staging_dir = env::var_os("TRUNK_STAGING_DIR")
+        .map(PathBuf::from)
+        .ok_or("TRUNK_STAGING_DIR is not set; this should be run as a Trunk hook")?;
+    let public_url = env::var("TRUNK_PUBLIC_URL").unwrap_or_else(|_| "/".to_string());
+    let public_url = if public_url.ends_with('/') {
+        public_url
+    } else {
+        format!("{public_url}/")
+    };
+
+    let mut files = Vec::new();
+    collect_files(&staging_dir, &staging_dir, &mut files)?;
+    files.retain(|file| file != OUTPUT);
+    files.sort();
+
+    // Trunk hashes asset names, but index.html and copied files keep theirs, so the version
+    // covers the contents too. Any change makes a new service worker, which the page then
+    // offers as an update.
+    let mut hasher = DefaultHasher::new();
+    for file in &files {
+        hasher.write(file.as_bytes());
+        hasher.write(&fs::read(staging_dir.join(file))?);
+    }
+    let version = format!("{:016x}", hasher.finish());
+
+    let assets = files
+        .iter()
+        .map(|file| format!("\"{public_url}{file}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let service_worker = TEMPLATE
+        .replace("__VERSION__", &version)
+        .replace("__ASSETS__", &format!("[{assets}]"))
+        .replace("__INDEX__", &format!("{public_url}index.html"));
+    fs::write(staging_dir.join(OUTPUT), service_worker)?;
+
+    eprintln!("Wrote {OUTPUT} caching {} files (version {version})", files.len());
+    Ok(())
+}
+
+/// Lists the files under a directory as URL paths relative to the root
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let relative = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push(relative);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+// Generated by sw_gen at build time. Caches every file of the build so the app works offline.
+
+const VERSION = "__VERSION__";
+const ASSETS = __ASSETS__;
+const INDEX = "__INDEX__";
+const CACHE = `mskey-web-${VERSION}`;
+
+self.addEventListener("install", (event) => {
+    event.waitUntil(caches.open(CACHE).then((cache) => cache.addAll(ASSETS)));
+});
+
+self.addEventListener("activate", (event) => {
+    event.waitUntil(
+        caches
+            .keys()
+            .then((keys) =>
+                Promise.all(
+                    keys
+                        .filter((key) => key.startsWith("mskey-web-") && key !== CACHE)
+                        .map((key) => caches.delete(key)),
+                ),
+            )
+            .then(() => self.clients.claim()),
+    );
+});
+
+// The page asks a waiting worker to take over when the user chooses to update
+self.addEventListener("message", (event) => {
+    if (event.data === "skipWaiting") {
+        self.skipWaiting();
+    }
+});
+
+self.addEventListener("fetch", (event) => {
+    const request = event.request;
+    if (request.method !== "GET") {
+        return;
+    }
+    // Every route is the same page, so navigations get the cached index.html
+    const lookup = request.mode === "navigate" ? INDEX : request;
+    event.respondWith(
+        caches
+            .open(CACHE)
+            .then((cache) => cache.match(lookup))
+            .then((response) => response || fetch(request)),
+    );
+});